@@ -0,0 +1,23 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MerkleError {
+    /// Depth is zero or above `MAX_DEPTH`.
+    InvalidDepth,
+    /// Proof bytes match neither the uncompressed nor the compressed encoding.
+    InvalidProofLength,
+    /// Two subjects share every key bit the tree uses and map to one leaf.
+    LeafCollision,
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerkleError::InvalidDepth => write!(f, "invalid tree depth"),
+            MerkleError::InvalidProofLength => write!(f, "invalid merkle proof length"),
+            MerkleError::LeafCollision => write!(f, "two subjects map to the same leaf"),
+        }
+    }
+}
+
+impl std::error::Error for MerkleError {}
@@ -0,0 +1,120 @@
+pub const MAX_DEPTH: u8 = 64;
+
+pub const LEAF_TAG: &[u8] = b"bouncer:leaf";
+pub const NODE_TAG: &[u8] = b"bouncer:node";
+
+/// Leaf of a subject that is not in the set.
+pub const ZERO_LEAF: [u8; 32] = [0u8; 32];
+
+/// Leaf position key: `sha256(subject)`. Bit `i` (little-endian) of it picks
+/// the side at level `i`, counting from the leaves.
+pub fn key_hash(subject: &[u8; 32]) -> [u8; 32] {
+    solana_sha256_hasher::hash(subject).to_bytes()
+}
+
+pub fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    solana_sha256_hasher::hashv(&[NODE_TAG, left.as_ref(), right.as_ref()]).to_bytes()
+}
+
+pub fn leaf_hash(key_hash: &[u8; 32], leaf_value: u8) -> [u8; 32] {
+    if leaf_value == 0 {
+        ZERO_LEAF
+    } else {
+        solana_sha256_hasher::hashv(&[LEAF_TAG, key_hash.as_ref(), &[1u8]]).to_bytes()
+    }
+}
+
+/// Metadata a leaf can commit to instead of the plain "present" bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeafData {
+    pub status: u8,       // same codes as Direct-PDA entries (1 = allow, 2 = block)
+    pub valid_until: i64, // unix timestamp (exclusive), 0 = no expiry
+    pub tier: u16,        // tier / jurisdiction code compared against the list minimum
+}
+
+impl LeafData {
+    /// Leaf committing to this metadata. The hashed message is longer than a
+    /// plain present leaf's, so the two can never coincide.
+    pub fn hash(&self, key_hash: &[u8; 32]) -> [u8; 32] {
+        solana_sha256_hasher::hashv(&[
+            LEAF_TAG,
+            key_hash.as_ref(),
+            &[self.status],
+            &self.valid_until.to_le_bytes(),
+            &self.tier.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+pub fn get_bit_le(bytes: &[u8; 32], bit_index: usize) -> bool {
+    let byte = bytes[bit_index / 8];
+    ((byte >> (bit_index % 8)) & 1) == 1
+}
+
+/// `empty[i]` is the root of an all-empty subtree of height `i`, for `i` in `0..=depth`.
+pub fn empty_subtree_hashes(depth: u8) -> Vec<[u8; 32]> {
+    let mut empty = Vec::with_capacity(depth as usize + 1);
+    let mut current = ZERO_LEAF;
+    empty.push(current);
+    for _ in 0..depth {
+        current = hash_node(&current, &current);
+        empty.push(current);
+    }
+    empty
+}
+
+/// Root of a tree of `depth` levels where every leaf is empty.
+pub fn empty_root(depth: u8) -> [u8; 32] {
+    let mut current = ZERO_LEAF;
+    for _ in 0..depth {
+        current = hash_node(&current, &current);
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+        solana_sha256_hasher::hashv(parts).to_bytes()
+    }
+
+    #[test]
+    fn empty_root_hashes_zero_leaves_pairwise() {
+        let level1 = sha256(&[b"bouncer:node", &[0u8; 32], &[0u8; 32]]);
+        let level2 = sha256(&[b"bouncer:node", &level1, &level1]);
+
+        assert_eq!(empty_root(1), level1);
+        assert_eq!(empty_root(2), level2);
+        assert_eq!(empty_subtree_hashes(2), vec![ZERO_LEAF, level1, level2]);
+    }
+
+    #[test]
+    fn leaf_hash_commits_to_key_and_value() {
+        let key = key_hash(&[7u8; 32]);
+
+        assert_eq!(leaf_hash(&key, 0), ZERO_LEAF);
+        assert_eq!(leaf_hash(&key, 1), sha256(&[b"bouncer:leaf", &key, &[1]]));
+
+        let data = LeafData {
+            status: 1,
+            valid_until: 0,
+            tier: 0,
+        };
+        assert_ne!(data.hash(&key), leaf_hash(&key, 1));
+    }
+
+    #[test]
+    fn key_bits_are_little_endian() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0b0000_0010;
+        bytes[1] = 0b0000_0001;
+
+        assert!(!get_bit_le(&bytes, 0));
+        assert!(get_bit_le(&bytes, 1));
+        assert!(get_bit_le(&bytes, 8));
+        assert!(!get_bit_le(&bytes, 9));
+    }
+}
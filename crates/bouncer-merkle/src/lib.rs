@@ -0,0 +1,13 @@
+//! Sparse Merkle tree rules shared by the bouncer program and off-chain
+//! tooling: the on-chain verifier and the tree builder here hash with the
+//! same functions, so roots and proofs agree byte for byte.
+
+mod error;
+mod hash;
+mod proof;
+mod tree;
+
+pub use error::MerkleError;
+pub use hash::*;
+pub use proof::*;
+pub use tree::SparseMerkleTree;
@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+
+use crate::{
+    error::MerkleError,
+    hash::{empty_subtree_hashes, get_bit_le, hash_node, leaf_hash, MAX_DEPTH},
+};
+
+fn check_depth(depth: u8) -> Result<(), MerkleError> {
+    if depth == 0 || depth > MAX_DEPTH {
+        return Err(MerkleError::InvalidDepth);
+    }
+    Ok(())
+}
+
+/// Bytes in the sibling bitmap of a compressed proof for `depth` levels.
+pub fn proof_bitmap_len(depth: u8) -> usize {
+    (depth as usize).div_ceil(8)
+}
+
+/// Normalize `proof` to the uncompressed `depth * 32`-byte form.
+///
+/// A compressed proof is a `proof_bitmap_len(depth)`-byte bitmap (bit `i`,
+/// little-endian, set when the level-`i` sibling is supplied) followed by the
+/// supplied siblings from level 0 upward. Missing siblings are empty subtrees.
+/// The bitmap length is never a multiple of 32, so the two encodings cannot be
+/// confused.
+pub fn expand_proof(depth: u8, proof: &[u8]) -> Result<Cow<'_, [u8]>, MerkleError> {
+    check_depth(depth)?;
+
+    let depth_usize = depth as usize;
+    if proof.len() == depth_usize * 32 {
+        return Ok(Cow::Borrowed(proof));
+    }
+
+    let bitmap_len = proof_bitmap_len(depth);
+    if proof.len() < bitmap_len {
+        return Err(MerkleError::InvalidProofLength);
+    }
+    let (bitmap, siblings) = proof.split_at(bitmap_len);
+
+    let supplied = |level: usize| (bitmap[level / 8] >> (level % 8)) & 1 == 1;
+    let unused_bits_set = (depth_usize..bitmap_len * 8).any(supplied);
+    let supplied_count = (0..depth_usize).filter(|&level| supplied(level)).count();
+    if unused_bits_set || siblings.len() != supplied_count * 32 {
+        return Err(MerkleError::InvalidProofLength);
+    }
+
+    let empty = empty_subtree_hashes(depth);
+    let mut expanded = Vec::with_capacity(depth_usize * 32);
+    let mut next_sibling = siblings.chunks_exact(32);
+    for (level, empty_sibling) in empty.iter().take(depth_usize).enumerate() {
+        if supplied(level) {
+            // Length was checked against the bitmap above
+            expanded.extend_from_slice(next_sibling.next().unwrap_or_default());
+        } else {
+            expanded.extend_from_slice(empty_sibling);
+        }
+    }
+
+    Ok(Cow::Owned(expanded))
+}
+
+/// Compress an uncompressed proof, omitting every sibling that is an empty subtree.
+pub fn compress_proof(depth: u8, proof: &[u8]) -> Result<Vec<u8>, MerkleError> {
+    check_depth(depth)?;
+    if proof.len() != depth as usize * 32 {
+        return Err(MerkleError::InvalidProofLength);
+    }
+
+    let empty = empty_subtree_hashes(depth);
+    let mut bitmap = vec![0u8; proof_bitmap_len(depth)];
+    let mut siblings = Vec::new();
+    for (level, sibling) in proof.chunks_exact(32).enumerate() {
+        if sibling != empty[level] {
+            bitmap[level / 8] |= 1 << (level % 8);
+            siblings.extend_from_slice(sibling);
+        }
+    }
+
+    bitmap.extend_from_slice(&siblings);
+    Ok(bitmap)
+}
+
+/// Nodes on the path from the leaf for `key_hash` up to the root:
+/// `path[0]` is the leaf, `path[depth]` is the root. `proof` may be in either
+/// encoding accepted by `expand_proof`.
+pub fn compute_path(
+    depth: u8,
+    key_hash: &[u8; 32],
+    leaf_value: u8,
+    proof: &[u8],
+) -> Result<Vec<[u8; 32]>, MerkleError> {
+    compute_path_for_leaf(depth, key_hash, leaf_hash(key_hash, leaf_value), proof)
+}
+
+/// `compute_path` for an already hashed leaf, e.g. `LeafData::hash`.
+pub fn compute_path_for_leaf(
+    depth: u8,
+    key_hash: &[u8; 32],
+    leaf: [u8; 32],
+    proof: &[u8],
+) -> Result<Vec<[u8; 32]>, MerkleError> {
+    let proof = expand_proof(depth, proof)?;
+
+    let mut path = Vec::with_capacity(depth as usize + 1);
+    let mut current = leaf;
+    path.push(current);
+
+    for (i, chunk) in proof.chunks_exact(32).enumerate() {
+        let mut sibling = [0u8; 32];
+        sibling.copy_from_slice(chunk);
+
+        current = if get_bit_le(key_hash, i) {
+            hash_node(&sibling, &current)
+        } else {
+            hash_node(&current, &sibling)
+        };
+        path.push(current);
+    }
+
+    Ok(path)
+}
+
+pub fn compute_root(
+    depth: u8,
+    key_hash: &[u8; 32],
+    leaf_value: u8,
+    proof: &[u8],
+) -> Result<[u8; 32], MerkleError> {
+    let path = compute_path(depth, key_hash, leaf_value, proof)?;
+    Ok(path[depth as usize])
+}
+
+pub fn compute_root_for_leaf(
+    depth: u8,
+    key_hash: &[u8; 32],
+    leaf: [u8; 32],
+    proof: &[u8],
+) -> Result<[u8; 32], MerkleError> {
+    let path = compute_path_for_leaf(depth, key_hash, leaf, proof)?;
+    Ok(path[depth as usize])
+}
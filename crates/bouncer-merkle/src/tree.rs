@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::MerkleError,
+    hash::{
+        empty_subtree_hashes, get_bit_le, hash_node, key_hash, leaf_hash, LeafData, MAX_DEPTH,
+    },
+    proof::compress_proof,
+};
+
+type Leaf<'a> = (&'a [u8; 32], &'a [u8; 32]);
+
+/// Off-chain builder for a bouncer sparse Merkle tree. Members hold either the
+/// plain present leaf (value 1) or a `LeafData` leaf; everyone else has the
+/// zero leaf. Plain leaves match what `assert_allowed` expects for allowlists
+/// (membership proofs) and blocklists (non-membership proofs).
+#[derive(Debug, Clone)]
+pub struct SparseMerkleTree {
+    depth: u8,
+    empty: Vec<[u8; 32]>,
+    leaves: BTreeMap<[u8; 32], [u8; 32]>, // key hash -> leaf hash
+}
+
+impl SparseMerkleTree {
+    pub fn new(depth: u8) -> Result<Self, MerkleError> {
+        if depth == 0 || depth > MAX_DEPTH {
+            return Err(MerkleError::InvalidDepth);
+        }
+        Ok(Self {
+            depth,
+            empty: empty_subtree_hashes(depth),
+            leaves: BTreeMap::new(),
+        })
+    }
+
+    /// Build a tree holding every subject in `subjects`.
+    pub fn from_subjects<'a>(
+        depth: u8,
+        subjects: impl IntoIterator<Item = &'a [u8; 32]>,
+    ) -> Result<Self, MerkleError> {
+        let mut tree = Self::new(depth)?;
+        for subject in subjects {
+            tree.insert(subject)?;
+        }
+        Ok(tree)
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn contains(&self, subject: &[u8; 32]) -> bool {
+        self.leaves.contains_key(&key_hash(subject))
+    }
+
+    /// Add `subject` with the plain present leaf. Fails if another member
+    /// already occupies its leaf.
+    pub fn insert(&mut self, subject: &[u8; 32]) -> Result<(), MerkleError> {
+        let key = key_hash(subject);
+        self.set_leaf(key, leaf_hash(&key, 1))
+    }
+
+    /// Add or update `subject` with a leaf committing to `data`.
+    pub fn insert_with_data(
+        &mut self,
+        subject: &[u8; 32],
+        data: &LeafData,
+    ) -> Result<(), MerkleError> {
+        let key = key_hash(subject);
+        self.set_leaf(key, data.hash(&key))
+    }
+
+    pub fn remove(&mut self, subject: &[u8; 32]) -> bool {
+        self.leaves.remove(&key_hash(subject)).is_some()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        let leaves: Vec<Leaf> = self.leaves.iter().collect();
+        self.subtree_root(&leaves, self.depth as usize)
+    }
+
+    /// Uncompressed proof (`depth * 32` bytes, level 0 first) for `subject`,
+    /// whether or not it is a member. A non-member whose leaf is taken by a
+    /// member cannot be proven absent at this depth.
+    pub fn proof(&self, subject: &[u8; 32]) -> Vec<u8> {
+        let key = key_hash(subject);
+        let mut leaves: Vec<Leaf> = self.leaves.iter().collect();
+        let mut siblings = vec![[0u8; 32]; self.depth as usize];
+
+        // Walk down from the root, keeping only leaves on `subject`'s side
+        for level in (0..self.depth as usize).rev() {
+            let bit = get_bit_le(&key, level);
+            let (same, other): (Vec<Leaf>, Vec<Leaf>) = leaves
+                .into_iter()
+                .partition(|(k, _)| get_bit_le(k, level) == bit);
+            siblings[level] = self.subtree_root(&other, level);
+            leaves = same;
+        }
+
+        siblings.concat()
+    }
+
+    /// Proof for `subject` in the bitmap-compressed encoding.
+    pub fn compressed_proof(&self, subject: &[u8; 32]) -> Vec<u8> {
+        // The uncompressed proof always has the right length for this depth
+        compress_proof(self.depth, &self.proof(subject)).unwrap_or_default()
+    }
+
+    fn set_leaf(&mut self, key: [u8; 32], leaf: [u8; 32]) -> Result<(), MerkleError> {
+        if !self.leaves.contains_key(&key)
+            && self.leaves.keys().any(|other| self.same_leaf(other, &key))
+        {
+            return Err(MerkleError::LeafCollision);
+        }
+        self.leaves.insert(key, leaf);
+        Ok(())
+    }
+
+    fn same_leaf(&self, a: &[u8; 32], b: &[u8; 32]) -> bool {
+        (0..self.depth as usize).all(|i| get_bit_le(a, i) == get_bit_le(b, i))
+    }
+
+    // Root of the subtree of `height` levels holding `leaves`, which all share
+    // the key bits above it.
+    fn subtree_root(&self, leaves: &[Leaf], height: usize) -> [u8; 32] {
+        match leaves {
+            [] => self.empty[height],
+            [(_, leaf)] if height == 0 => **leaf,
+            _ => {
+                let (left, right): (Vec<Leaf>, Vec<Leaf>) = leaves
+                    .iter()
+                    .copied()
+                    .partition(|(k, _)| !get_bit_le(k, height - 1));
+                hash_node(
+                    &self.subtree_root(&left, height - 1),
+                    &self.subtree_root(&right, height - 1),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::{compute_root, compute_root_for_leaf, expand_proof};
+
+    const DEPTH: u8 = 20;
+
+    fn subject(i: u8) -> [u8; 32] {
+        [i; 32]
+    }
+
+    fn members() -> SparseMerkleTree {
+        let subjects: Vec<[u8; 32]> = (1..=6).map(subject).collect();
+        SparseMerkleTree::from_subjects(DEPTH, subjects.iter()).unwrap()
+    }
+
+    #[test]
+    fn empty_tree_has_empty_root() {
+        let tree = SparseMerkleTree::new(DEPTH).unwrap();
+        assert_eq!(tree.root(), crate::hash::empty_root(DEPTH));
+    }
+
+    #[test]
+    fn rejects_invalid_depth() {
+        assert_eq!(
+            SparseMerkleTree::new(0).unwrap_err(),
+            MerkleError::InvalidDepth
+        );
+        assert_eq!(
+            SparseMerkleTree::new(MAX_DEPTH + 1).unwrap_err(),
+            MerkleError::InvalidDepth
+        );
+    }
+
+    #[test]
+    fn membership_proofs_verify_against_root() {
+        let tree = members();
+        let root = tree.root();
+
+        for i in 1..=6 {
+            let key = key_hash(&subject(i));
+            let proof = tree.proof(&subject(i));
+            assert_eq!(proof.len(), DEPTH as usize * 32);
+            assert_eq!(compute_root(DEPTH, &key, 1, &proof).unwrap(), root);
+            assert_ne!(compute_root(DEPTH, &key, 0, &proof).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn non_membership_proofs_verify_against_root() {
+        let tree = members();
+        let root = tree.root();
+
+        for i in 7..=12 {
+            let key = key_hash(&subject(i));
+            let proof = tree.proof(&subject(i));
+            assert_eq!(compute_root(DEPTH, &key, 0, &proof).unwrap(), root);
+            assert_ne!(compute_root(DEPTH, &key, 1, &proof).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn compressed_proofs_expand_to_uncompressed() {
+        let tree = members();
+        let root = tree.root();
+
+        for i in 1..=12 {
+            let key = key_hash(&subject(i));
+            let proof = tree.proof(&subject(i));
+            let compressed = tree.compressed_proof(&subject(i));
+            assert!(compressed.len() < proof.len());
+            assert_eq!(expand_proof(DEPTH, &compressed).unwrap(), proof.as_slice());
+
+            let value = u8::from(tree.contains(&subject(i)));
+            assert_eq!(compute_root(DEPTH, &key, value, &compressed).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn data_leaves_verify_with_compute_root_for_leaf() {
+        let mut tree = members();
+        let data = LeafData {
+            status: 1,
+            valid_until: 1_900_000_000,
+            tier: 3,
+        };
+        tree.insert_with_data(&subject(9), &data).unwrap();
+        let root = tree.root();
+
+        let key = key_hash(&subject(9));
+        let proof = tree.proof(&subject(9));
+        assert_eq!(
+            compute_root_for_leaf(DEPTH, &key, data.hash(&key), &proof).unwrap(),
+            root
+        );
+        assert_ne!(compute_root(DEPTH, &key, 1, &proof).unwrap(), root);
+    }
+
+    #[test]
+    fn remove_restores_previous_root() {
+        let mut tree = members();
+        let before = tree.root();
+
+        tree.insert(&subject(20)).unwrap();
+        assert_ne!(tree.root(), before);
+        assert!(tree.remove(&subject(20)));
+        assert_eq!(tree.root(), before);
+    }
+
+    #[test]
+    fn rejects_subjects_sharing_a_leaf() {
+        // At depth 1 there are two leaves, so some pair of three subjects shares one
+        let mut tree = SparseMerkleTree::new(1).unwrap();
+        let results: Vec<_> = (1..=3).map(|i| tree.insert(&subject(i))).collect();
+        assert!(results.contains(&Err(MerkleError::LeafCollision)));
+
+        // Re-inserting a member only replaces its own leaf
+        assert_eq!(tree.insert(&subject(1)), Ok(()));
+    }
+}
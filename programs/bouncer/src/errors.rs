@@ -28,4 +28,36 @@ pub enum BouncerError {
     InvalidProofLength,
     #[msg("Invalid merkle proof")]
     InvalidMerkleProof,
+    #[msg("Entry validity window is invalid")]
+    InvalidValidityWindow,
+    #[msg("Invalid delegate permissions")]
+    InvalidPermissions,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Batch argument and account counts do not match")]
+    BatchLengthMismatch,
+    #[msg("List still has entries")]
+    ListNotEmpty,
+    #[msg("Invalid combinator value")]
+    InvalidCombinator,
+    #[msg("Invalid group member lists")]
+    InvalidGroupMembers,
+    #[msg("Root is not in the tree changelog")]
+    StaleRoot,
+    #[msg("Leaf was modified since the proof's root")]
+    LeafConcurrentlyModified,
+    #[msg("Receipt does not belong to this list and subject")]
+    InvalidReceipt,
+    #[msg("Receipt root is no longer accepted by the list")]
+    StaleReceipt,
+    #[msg("Receipt still verifies")]
+    ReceiptStillValid,
+    #[msg("List still has delegates")]
+    DelegatesRemaining,
+    #[msg("List root history or tree account is still open")]
+    ListAccountsOpen,
+    #[msg("List has a root history that must be passed")]
+    MissingRootHistory,
+    #[msg("Root history needs a max age")]
+    RootHistoryMaxAgeRequired,
 }
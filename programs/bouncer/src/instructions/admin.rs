@@ -3,13 +3,14 @@ use anchor_lang::prelude::*;
 use crate::{
     errors::BouncerError,
     state::{
-        List, AuthorityChanged, PolicyChanged, ListFrozen, FLAG_FROZEN, POLICY_ALLOWLIST,
-        POLICY_BLOCKLIST,
+        List, AuthorityChanged, AuthorityTransferCancelled, AuthorityTransferProposed, Delegate,
+        ListClosed, PolicyChanged, ListFrozen, FLAG_FROZEN, PERM_ADMIN, POLICY_ALLOWLIST,
+        POLICY_BLOCKLIST, STORAGE_DIRECT_PDA,
     },
 };
 
 #[derive(Accounts)]
-pub struct SetAuthority<'info> {
+pub struct ProposeAuthority<'info> {
     pub authority: Signer<'info>,
 
     #[account(
@@ -22,10 +23,37 @@ pub struct SetAuthority<'info> {
     pub list: Account<'info, List>,
 }
 
-pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
+pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    list.pending_authority = new_authority;
+    emit!(AuthorityTransferProposed {
+        list: list.key(),
+        authority: list.authority,
+        pending_authority: new_authority,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = pending_authority @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+}
+
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
     let list = &mut ctx.accounts.list;
     let old = list.authority;
+    let new_authority = list.pending_authority;
     list.authority = new_authority;
+    list.pending_authority = Pubkey::default();
     emit!(AuthorityChanged {
         list: list.key(),
         old_authority: old,
@@ -35,17 +63,50 @@ pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Resul
 }
 
 #[derive(Accounts)]
-pub struct SetPolicy<'info> {
+pub struct CancelAuthorityTransfer<'info> {
     pub authority: Signer<'info>,
 
     #[account(
         mut,
         has_one = authority @ BouncerError::Unauthorized,
+        constraint = list.pending_authority != Pubkey::default() @ BouncerError::NoPendingAuthority,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+}
+
+pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    let pending = list.pending_authority;
+    list.pending_authority = Pubkey::default();
+    emit!(AuthorityTransferCancelled {
+        list: list.key(),
+        authority: list.authority,
+        pending_authority: pending,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPolicy<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ADMIN)
+            @ BouncerError::Unauthorized,
         constraint = !list.is_frozen() @ BouncerError::Frozen,
         seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
         bump = list.bump
     )]
     pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
 }
 
 pub fn set_policy(ctx: Context<SetPolicy>, new_policy: u8) -> Result<()> {
@@ -56,6 +117,7 @@ pub fn set_policy(ctx: Context<SetPolicy>, new_policy: u8) -> Result<()> {
     let list = &mut ctx.accounts.list;
     let old = list.policy;
     list.policy = new_policy;
+    list.bump_revision();
     emit!(PolicyChanged {
         list: list.key(),
         old_policy: old,
@@ -70,12 +132,19 @@ pub struct FreezeList<'info> {
 
     #[account(
         mut,
-        has_one = authority @ BouncerError::Unauthorized,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ADMIN)
+            @ BouncerError::Unauthorized,
         constraint = !list.is_frozen() @ BouncerError::Frozen,
         seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
         bump = list.bump
     )]
     pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
 }
 
 pub fn freeze_list(ctx: Context<FreezeList>) -> Result<()> {
@@ -85,3 +154,53 @@ pub fn freeze_list(ctx: Context<FreezeList>) -> Result<()> {
     Ok(())
 }
 
+
+// Frozen lists cannot be closed: freezing is a promise that the list stays
+// as-is. Delegates, the RootHistory and the CompressedTree must be gone first,
+// or a list re-created at the same address would inherit them.
+#[derive(Accounts)]
+pub struct CloseList<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        has_one = authority @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind != STORAGE_DIRECT_PDA || list.entry_count == 0
+            @ BouncerError::ListNotEmpty,
+        constraint = list.delegate_count == 0 @ BouncerError::DelegatesRemaining,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    /// CHECK: RootHistory PDA ["roots", list]; must not exist
+    #[account(
+        seeds = [b"roots", list.key().as_ref()],
+        bump,
+        constraint = root_history.data_is_empty() @ BouncerError::ListAccountsOpen
+    )]
+    pub root_history: UncheckedAccount<'info>,
+
+    /// CHECK: CompressedTree PDA ["tree", list]; must not exist
+    #[account(
+        seeds = [b"tree", list.key().as_ref()],
+        bump,
+        constraint = tree.data_is_empty() @ BouncerError::ListAccountsOpen
+    )]
+    pub tree: UncheckedAccount<'info>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+pub fn close_list(ctx: Context<CloseList>) -> Result<()> {
+    emit!(ListClosed {
+        list: ctx.accounts.list.key(),
+        authority: ctx.accounts.authority.key(),
+        refund_to: ctx.accounts.refund_to.key(),
+    });
+    Ok(())
+}
@@ -2,9 +2,17 @@ use anchor_lang::prelude::*;
 
 use crate::{
     errors::BouncerError,
-    merkle::{expected_leaf_value_for_policy, verify_sparse_merkle_proof},
+    instructions::tree_proves_leaf,
+    merkle::{
+        compute_sparse_merkle_root_for_leaf, expected_leaf_value_for_policy, key_hash, leaf_hash,
+        leaf_node,
+    },
     state::{
-        Entry, List, POLICY_ALLOWLIST, POLICY_BLOCKLIST, STORAGE_DIRECT_PDA, STORAGE_MERKLE_ROOT,
+        CompressedTree, Entry, LeafData, List, Receipt, RootHistory, Verdict, ENTRY_STATUS_ALLOW,
+        ENTRY_STATUS_BLOCK, ENTRY_STATUS_UNSET, POLICY_ALLOWLIST, POLICY_BLOCKLIST,
+        REASON_ALLOWED, REASON_BLOCKED, REASON_ENTRY_MISMATCH, REASON_INVALID_LIST,
+        REASON_INVALID_PROOF, REASON_NOT_LISTED, REASON_OUTSIDE_VALIDITY, REASON_TIER_TOO_LOW,
+        STORAGE_COMPRESSED_TREE, STORAGE_DIRECT_PDA, STORAGE_MERKLE_ROOT,
     },
 };
 
@@ -17,8 +25,130 @@ pub struct AssertAllowed<'info> {
     pub list: Account<'info, List>,
 }
 
-pub fn assert_allowed(ctx: Context<AssertAllowed>, subject: Pubkey, proof: Vec<u8>) -> Result<()> {
+fn is_uninitialized(info: &AccountInfo) -> bool {
+    *info.owner == System::id() && info.data_is_empty()
+}
+
+pub fn assert_allowed(
+    ctx: Context<AssertAllowed>,
+    subject: Pubkey,
+    proof: Vec<u8>,
+    leaf: Option<LeafData>,
+) -> Result<()> {
+    let list = &ctx.accounts.list;
+    let allowed = is_allowed(
+        ctx.program_id,
+        &list.key(),
+        list,
+        subject,
+        &proof,
+        leaf.as_ref(),
+        ctx.remaining_accounts,
+    )?;
+    require!(allowed, BouncerError::NotAllowed);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProveStatus<'info> {
+    #[account(
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+}
+
+/// View: does `proof` show `subject`'s leaf holding `leaf_value` (0 = absent,
+/// 1 = present, committing to `leaf` when given), regardless of list policy?
+/// The answer is returned as return data; a proof that simply does not match
+/// yields `false` instead of an error.
+pub fn prove_status(
+    ctx: Context<ProveStatus>,
+    subject: Pubkey,
+    leaf_value: u8,
+    proof: Vec<u8>,
+    leaf: Option<LeafData>,
+) -> Result<bool> {
+    let list = &ctx.accounts.list;
+    let key_hash = key_hash(&subject.to_bytes());
+    proves_leaf(
+        ctx.program_id,
+        &list.key(),
+        list,
+        key_hash,
+        leaf_node(&key_hash, leaf_value, leaf.as_ref())?,
+        &proof,
+        ctx.remaining_accounts,
+    )
+}
+
+#[derive(Accounts)]
+pub struct CheckStatus<'info> {
+    #[account(
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+}
+
+/// View: `assert_allowed` that never fails on a denial or a bad entry/proof,
+/// returning a `Verdict` as return data instead. Takes the same accounts and
+/// arguments as `assert_allowed`.
+pub fn check_status(
+    ctx: Context<CheckStatus>,
+    subject: Pubkey,
+    proof: Vec<u8>,
+    leaf: Option<LeafData>,
+) -> Result<Verdict> {
     let list = &ctx.accounts.list;
+    let verdict = evaluate(
+        ctx.program_id,
+        &list.key(),
+        list,
+        subject,
+        &proof,
+        leaf.as_ref(),
+        ctx.remaining_accounts,
+    )
+    .unwrap_or_else(|err| Verdict {
+        allowed: false,
+        reason: reason_for_error(&err),
+        entry_status: ENTRY_STATUS_UNSET,
+        list_revision: list.revision,
+    });
+    Ok(verdict)
+}
+
+/// Evaluate `subject` against a single list. `aux_accounts` are the list's
+/// auxiliary accounts: the optional Entry PDA for Direct-PDA lists; for Merkle
+/// lists the optional RootHistory or the required tree account, plus
+/// optionally the subject's Receipt, which stands in for an empty `proof`.
+/// `leaf` is the metadata a Merkle leaf commits to, if it is not a plain
+/// present/absent leaf. Malformed inputs are errors; a clean "not on the list"
+/// is `Ok(false)`.
+pub fn is_allowed(
+    program_id: &Pubkey,
+    list_key: &Pubkey,
+    list: &List,
+    subject: Pubkey,
+    proof: &[u8],
+    leaf: Option<&LeafData>,
+    aux_accounts: &[AccountInfo],
+) -> Result<bool> {
+    evaluate(program_id, list_key, list, subject, proof, leaf, aux_accounts)
+        .map(|verdict| verdict.allowed)
+}
+
+/// `is_allowed` with the reason behind the answer.
+pub fn evaluate(
+    program_id: &Pubkey,
+    list_key: &Pubkey,
+    list: &List,
+    subject: Pubkey,
+    proof: &[u8],
+    leaf: Option<&LeafData>,
+    aux_accounts: &[AccountInfo],
+) -> Result<Verdict> {
     require!(
         list.policy == POLICY_ALLOWLIST || list.policy == POLICY_BLOCKLIST,
         BouncerError::InvalidPolicy
@@ -26,54 +156,307 @@ pub fn assert_allowed(ctx: Context<AssertAllowed>, subject: Pubkey, proof: Vec<u
 
     match list.storage_kind {
         STORAGE_DIRECT_PDA => {
-            require!(proof.is_empty(), BouncerError::ProofNotSupported);
+            require!(
+                proof.is_empty() && leaf.is_none(),
+                BouncerError::ProofNotSupported
+            );
 
             let expected_entry = Pubkey::find_program_address(
-                &[b"entry", list.key().as_ref(), subject.as_ref()],
-                ctx.program_id,
+                &[b"entry", list_key.as_ref(), subject.as_ref()],
+                program_id,
             )
             .0;
 
-            let mut status = 0u8;
+            let mut recorded = ENTRY_STATUS_UNSET;
+            let mut status = ENTRY_STATUS_UNSET;
 
-            // Optional entry PDA is passed as remaining account 0.
-            if let Some(entry_info) = ctx.remaining_accounts.first() {
+            // Optional entry PDA (remaining account 0 for assert_allowed). An uninitialized
+            // (system-owned, empty) account at the expected address means "no entry".
+            if let Some(entry_info) = aux_accounts.first() {
                 require_keys_eq!(entry_info.key(), expected_entry, BouncerError::EntryPdaMismatch);
-                require_keys_eq!(
-                    *entry_info.owner,
-                    *ctx.program_id,
-                    BouncerError::EntryAccountInvalidOwner
-                );
 
-                let data = entry_info.try_borrow_data()?;
-                let mut data_slice: &[u8] = &data;
-                let entry = Entry::try_deserialize(&mut data_slice)
-                    .map_err(|_| error!(BouncerError::EntryDataMismatch))?;
+                if !is_uninitialized(entry_info) {
+                    require_keys_eq!(
+                        *entry_info.owner,
+                        *program_id,
+                        BouncerError::EntryAccountInvalidOwner
+                    );
 
-                require_keys_eq!(entry.list, list.key(), BouncerError::EntryDataMismatch);
-                require_keys_eq!(entry.subject, subject, BouncerError::EntryDataMismatch);
+                    let data = entry_info.try_borrow_data()?;
+                    let mut data_slice: &[u8] = &data;
+                    let entry = Entry::try_deserialize(&mut data_slice)
+                        .map_err(|_| error!(BouncerError::EntryDataMismatch))?;
 
-                status = entry.status;
+                    require_keys_eq!(entry.list, *list_key, BouncerError::EntryDataMismatch);
+                    require_keys_eq!(entry.subject, subject, BouncerError::EntryDataMismatch);
+
+                    recorded = entry.status;
+                    // Expired or not-yet-valid entries count as unset
+                    status = entry.status_at(Clock::get()?.unix_timestamp);
+                }
             }
 
-            let allowed = match list.policy {
-                POLICY_ALLOWLIST => status == crate::state::ENTRY_STATUS_ALLOW,
-                POLICY_BLOCKLIST => status != crate::state::ENTRY_STATUS_BLOCK,
-                _ => return err!(BouncerError::InvalidPolicy),
+            status_verdict(list, recorded, status)
+        }
+        STORAGE_MERKLE_ROOT | STORAGE_COMPRESSED_TREE => {
+            let receipt = if proof.is_empty() {
+                load_aux::<Receipt>(program_id, aux_accounts)?
+            } else {
+                None
+            };
+
+            let leaf = match receipt {
+                // A receipt vouches for a proof verified earlier
+                Some(receipt) => {
+                    require_keys_eq!(receipt.list, *list_key, BouncerError::InvalidReceipt);
+                    require_keys_eq!(receipt.subject, subject, BouncerError::InvalidReceipt);
+                    require!(
+                        leaf.is_none() || leaf == receipt.leaf.as_ref(),
+                        BouncerError::InvalidReceipt
+                    );
+                    require!(
+                        receipt_is_current(program_id, list_key, list, &receipt, aux_accounts)?,
+                        BouncerError::StaleReceipt
+                    );
+                    receipt.leaf
+                }
+                None => {
+                    let key_hash = key_hash(&subject.to_bytes());
+                    let claimed = claimed_leaf(list, &key_hash, leaf)?;
+                    let proven = proves_leaf(
+                        program_id,
+                        list_key,
+                        list,
+                        key_hash,
+                        claimed,
+                        proof,
+                        aux_accounts,
+                    )?;
+                    require!(proven, BouncerError::InvalidMerkleProof);
+                    leaf.copied()
+                }
+            };
+
+            leaf_verdict(list, leaf.as_ref())
+        }
+        _ => err!(BouncerError::InvalidStorageKind),
+    }
+}
+
+/// Hash of the leaf a Merkle proof for this list must show: the `leaf`
+/// metadata if given, otherwise the plain leaf the policy expects (present
+/// for allowlists, absent for blocklists).
+pub fn claimed_leaf(list: &List, key_hash: &[u8; 32], leaf: Option<&LeafData>) -> Result<[u8; 32]> {
+    match leaf {
+        Some(leaf) => Ok(leaf.hash(key_hash)),
+        None => Ok(leaf_hash(key_hash, expected_leaf_value_for_policy(list.policy)?)),
+    }
+}
+
+/// True while `receipt` still verifies: its root is accepted by the list and
+/// it proves the leaf the list's current policy expects.
+pub fn receipt_is_current(
+    program_id: &Pubkey,
+    list_key: &Pubkey,
+    list: &List,
+    receipt: &Receipt,
+    aux_accounts: &[AccountInfo],
+) -> Result<bool> {
+    let key_hash = key_hash(&receipt.subject.to_bytes());
+    if claimed_leaf(list, &key_hash, receipt.leaf.as_ref())? != receipt.leaf_hash {
+        return Ok(false);
+    }
+    root_accepted(program_id, list_key, list, &key_hash, &receipt.root, aux_accounts)
+}
+
+// Verdict for a Merkle subject whose (plain or metadata) leaf has been proven
+fn leaf_verdict(list: &List, leaf: Option<&LeafData>) -> Result<Verdict> {
+    match leaf {
+        Some(leaf) => {
+            let status = leaf.status_at(Clock::get()?.unix_timestamp);
+            let verdict = status_verdict(list, leaf.status, status)?;
+            Ok(apply_min_tier(list, verdict, leaf.tier))
+        }
+        None => {
+            let recorded = match list.policy {
+                POLICY_ALLOWLIST => ENTRY_STATUS_ALLOW,
+                _ => ENTRY_STATUS_UNSET,
             };
+            // A plain leaf carries no tier, so it only passes an untiered allowlist
+            let verdict = status_verdict(list, recorded, recorded)?;
+            Ok(apply_min_tier(list, verdict, 0))
+        }
+    }
+}
+
+// Verdict for a subject whose entry records `recorded` and has `status` in
+// effect now (unset if outside its validity window).
+fn status_verdict(list: &List, recorded: u8, status: u8) -> Result<Verdict> {
+    let (allowed, reason) = match list.policy {
+        POLICY_ALLOWLIST => match status {
+            ENTRY_STATUS_ALLOW => (true, REASON_ALLOWED),
+            ENTRY_STATUS_BLOCK => (false, REASON_BLOCKED),
+            _ if recorded != ENTRY_STATUS_UNSET => (false, REASON_OUTSIDE_VALIDITY),
+            _ => (false, REASON_NOT_LISTED),
+        },
+        POLICY_BLOCKLIST => match status {
+            ENTRY_STATUS_BLOCK => (false, REASON_BLOCKED),
+            _ => (true, REASON_ALLOWED),
+        },
+        _ => return err!(BouncerError::InvalidPolicy),
+    };
+
+    Ok(Verdict {
+        allowed,
+        reason,
+        entry_status: recorded,
+        list_revision: list.revision,
+    })
+}
 
-            require!(allowed, BouncerError::NotAllowed);
-            Ok(())
+// Merkle allowlists may require a minimum leaf tier
+fn apply_min_tier(list: &List, verdict: Verdict, tier: u16) -> Verdict {
+    if verdict.allowed && list.policy == POLICY_ALLOWLIST && tier < list.merkle_min_tier() {
+        Verdict {
+            allowed: false,
+            reason: REASON_TIER_TOO_LOW,
+            ..verdict
         }
+    } else {
+        verdict
+    }
+}
+
+// Reason reported by check_status when evaluation itself fails
+fn reason_for_error(err: &Error) -> u8 {
+    let code = match err {
+        Error::AnchorError(err) => err.error_code_number,
+        Error::ProgramError(_) => return REASON_INVALID_LIST,
+    };
+
+    let entry_errors = [
+        BouncerError::EntryPdaMismatch,
+        BouncerError::EntryAccountInvalidOwner,
+        BouncerError::EntryDataMismatch,
+    ];
+    let proof_errors = [
+        BouncerError::ProofNotSupported,
+        BouncerError::InvalidProofLength,
+        BouncerError::InvalidMerkleProof,
+        BouncerError::InvalidReceipt,
+        BouncerError::StaleReceipt,
+    ];
+
+    if entry_errors.into_iter().any(|e| u32::from(e) == code) {
+        REASON_ENTRY_MISMATCH
+    } else if proof_errors.into_iter().any(|e| u32::from(e) == code) {
+        REASON_INVALID_PROOF
+    } else {
+        REASON_INVALID_LIST
+    }
+}
+
+/// Whether `proof` shows `leaf` at `key_hash` under a root the list accepts.
+/// Compressed-tree proofs are fast-forwarded through the changelog and must
+/// hold against the current root.
+pub fn proves_leaf(
+    program_id: &Pubkey,
+    list_key: &Pubkey,
+    list: &List,
+    key_hash: [u8; 32],
+    leaf: [u8; 32],
+    proof: &[u8],
+    aux_accounts: &[AccountInfo],
+) -> Result<bool> {
+    if list.storage_kind == STORAGE_COMPRESSED_TREE {
+        return with_tree(program_id, list_key, aux_accounts, |tree| {
+            tree_proves_leaf(tree, key_hash, leaf, proof)
+        });
+    }
+
+    let computed =
+        compute_sparse_merkle_root_for_leaf(list.merkle_depth(), key_hash, leaf, proof)?;
+    root_accepted(program_id, list_key, list, &key_hash, &computed, aux_accounts)
+}
+
+/// Whether a proof for `key_hash` against `root` still holds for the list.
+/// For Merkle-root lists `root` must be the current root or, if the
+/// RootHistory is among `aux_accounts`, a recent one; for compressed-tree
+/// lists the tree account ["tree", list] is required and `root` must be in its
+/// changelog with no later change to `key_hash`.
+pub fn root_accepted(
+    program_id: &Pubkey,
+    list_key: &Pubkey,
+    list: &List,
+    key_hash: &[u8; 32],
+    root: &[u8; 32],
+    aux_accounts: &[AccountInfo],
+) -> Result<bool> {
+    match list.storage_kind {
         STORAGE_MERKLE_ROOT => {
-            let depth = list.merkle_depth();
-            let root = list.merkle_root();
-            let expected_leaf_value = expected_leaf_value_for_policy(list.policy)?;
-            let key_hash = solana_sha256_hasher::hash(subject.as_ref()).to_bytes();
-            verify_sparse_merkle_proof(root, depth, key_hash, expected_leaf_value, &proof)?;
-            Ok(())
+            if *root == list.merkle_root() {
+                return Ok(true);
+            }
+
+            // Otherwise the proof may target a recently superseded root
+            match load_aux::<RootHistory>(program_id, aux_accounts)? {
+                Some(history) => {
+                    require_keys_eq!(history.list, *list_key, BouncerError::InvalidMerkleConfig);
+                    Ok(history.contains(root, list.merkle_depth(), Clock::get()?.slot))
+                }
+                None => Ok(false),
+            }
         }
+        STORAGE_COMPRESSED_TREE => with_tree(program_id, list_key, aux_accounts, |tree| {
+            Ok(tree.leaf_unchanged_since(root, key_hash))
+        }),
         _ => err!(BouncerError::InvalidStorageKind),
-    }?;
-    Ok(())
+    }
+}
+
+// Run `f` on the list's CompressedTree, which must be among `aux_accounts`
+fn with_tree<R>(
+    program_id: &Pubkey,
+    list_key: &Pubkey,
+    aux_accounts: &[AccountInfo],
+    f: impl FnOnce(&CompressedTree) -> Result<R>,
+) -> Result<R> {
+    let tree_info = aux_accounts
+        .iter()
+        .find(|info| is_program_account(program_id, info, CompressedTree::DISCRIMINATOR))
+        .ok_or(BouncerError::InvalidMerkleConfig)?;
+    let data = tree_info.try_borrow_data()?;
+    require!(
+        data.len() >= CompressedTree::LEN,
+        BouncerError::InvalidMerkleConfig
+    );
+    let tree: &CompressedTree = bytemuck::from_bytes(&data[8..CompressedTree::LEN]);
+    require_keys_eq!(tree.list, *list_key, BouncerError::InvalidMerkleConfig);
+    f(tree)
+}
+
+fn is_program_account(program_id: &Pubkey, info: &AccountInfo, discriminator: &[u8]) -> bool {
+    info.owner == program_id
+        && info
+            .try_borrow_data()
+            .is_ok_and(|data| data.starts_with(discriminator))
+}
+
+// First account of type `T` among the auxiliary accounts. Anything else
+// passed alongside (e.g. an unused entry PDA from a generic caller) is ignored.
+fn load_aux<T: AccountDeserialize + Discriminator>(
+    program_id: &Pubkey,
+    aux_accounts: &[AccountInfo],
+) -> Result<Option<T>> {
+    let Some(info) = aux_accounts
+        .iter()
+        .find(|info| is_program_account(program_id, info, T::DISCRIMINATOR))
+    else {
+        return Ok(None);
+    };
+    let data = info.try_borrow_data()?;
+    let mut data_slice: &[u8] = &data;
+    let account =
+        T::try_deserialize(&mut data_slice).map_err(|_| error!(BouncerError::InvalidMerkleConfig))?;
+    Ok(Some(account))
 }
@@ -0,0 +1,290 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
+
+use crate::{
+    errors::BouncerError,
+    instructions::entry::check_validity_window,
+    state::{
+        Delegate, Entry, EntryRemoved, EntryUpserted, List, ENTRY_STATUS_ALLOW,
+        ENTRY_STATUS_BLOCK, ENTRY_VERSION, PERM_ENTRY_OPERATOR, STORAGE_DIRECT_PDA,
+    },
+};
+
+// Batch variants of upsert_entry/remove_entry. Entry PDAs are passed in
+// remaining_accounts (writable), one per subject and in the same order.
+// Validity bounds are per subject too; when a vector is omitted, existing
+// entries keep that bound and new ones are unbounded.
+
+#[derive(Accounts)]
+pub struct UpsertEntries<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ENTRY_OPERATOR)
+            @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind == STORAGE_DIRECT_PDA @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn upsert_entries<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpsertEntries<'info>>,
+    subjects: Vec<Pubkey>,
+    statuses: Vec<u8>,
+    valid_froms: Option<Vec<i64>>,
+    valid_untils: Option<Vec<i64>>,
+) -> Result<()> {
+    require!(
+        subjects.len() == statuses.len() && subjects.len() == ctx.remaining_accounts.len(),
+        BouncerError::BatchLengthMismatch
+    );
+    require!(
+        valid_froms.as_ref().is_none_or(|v| v.len() == subjects.len())
+            && valid_untils.as_ref().is_none_or(|v| v.len() == subjects.len()),
+        BouncerError::BatchLengthMismatch
+    );
+
+    let list_key = ctx.accounts.list.key();
+    let mut created = 0u32;
+
+    for (i, ((subject, status), entry_info)) in subjects
+        .iter()
+        .zip(statuses.iter().copied())
+        .zip(ctx.remaining_accounts.iter())
+        .enumerate()
+    {
+        let valid_from = valid_froms.as_ref().map(|v| v[i]);
+        let valid_until = valid_untils.as_ref().map(|v| v[i]);
+
+        require!(
+            status == ENTRY_STATUS_ALLOW || status == ENTRY_STATUS_BLOCK,
+            BouncerError::InvalidStatus
+        );
+
+        let (expected_entry, bump) = Pubkey::find_program_address(
+            &[b"entry", list_key.as_ref(), subject.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(entry_info.key(), expected_entry, BouncerError::EntryPdaMismatch);
+
+        let entry = if *entry_info.owner == System::id() {
+            create_entry_account(&ctx, entry_info, subject, bump)?;
+            created = created.saturating_add(1);
+            Entry {
+                version: ENTRY_VERSION,
+                bump,
+                status,
+                reserved: 0,
+                list: list_key,
+                subject: *subject,
+                valid_from: valid_from.unwrap_or(0),
+                valid_until: valid_until.unwrap_or(0),
+            }
+        } else {
+            require_keys_eq!(
+                *entry_info.owner,
+                *ctx.program_id,
+                BouncerError::EntryAccountInvalidOwner
+            );
+            let data = entry_info.try_borrow_data()?;
+            let mut data_slice: &[u8] = &data;
+            let mut entry = Entry::try_deserialize(&mut data_slice)
+                .map_err(|_| error!(BouncerError::EntryDataMismatch))?;
+            require_keys_eq!(entry.list, list_key, BouncerError::EntryDataMismatch);
+            require_keys_eq!(entry.subject, *subject, BouncerError::EntryDataMismatch);
+            entry.status = status;
+            entry.valid_from = valid_from.unwrap_or(entry.valid_from);
+            entry.valid_until = valid_until.unwrap_or(entry.valid_until);
+            entry
+        };
+
+        check_validity_window(entry.valid_from, entry.valid_until)?;
+
+        let mut data = entry_info.try_borrow_mut_data()?;
+        let mut data_slice: &mut [u8] = &mut data;
+        entry.try_serialize(&mut data_slice)?;
+
+        emit!(EntryUpserted {
+            list: list_key,
+            subject: *subject,
+            status,
+            valid_from: entry.valid_from,
+            valid_until: entry.valid_until,
+        });
+    }
+
+    let list = &mut ctx.accounts.list;
+    list.entry_count = list.entry_count.saturating_add(created);
+    list.bump_revision();
+
+    Ok(())
+}
+
+// Create the Entry PDA owned by this program, funding rent from the payer.
+// Handles a pre-funded address the same way Anchor's `init` does.
+fn create_entry_account<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, UpsertEntries<'info>>,
+    entry_info: &AccountInfo<'info>,
+    subject: &Pubkey,
+    bump: u8,
+) -> Result<()> {
+    let list_key = ctx.accounts.list.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"entry", list_key.as_ref(), subject.as_ref(), &[bump]]];
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let payer = ctx.accounts.payer.to_account_info();
+    let rent = Rent::get()?.minimum_balance(Entry::LEN);
+    let current_lamports = entry_info.lamports();
+
+    if current_lamports == 0 {
+        create_account(
+            CpiContext::new(
+                system_program,
+                CreateAccount {
+                    from: payer,
+                    to: entry_info.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            rent,
+            Entry::LEN as u64,
+            ctx.program_id,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(current_lamports);
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer,
+                        to: entry_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        allocate(
+            CpiContext::new(
+                system_program.clone(),
+                Allocate {
+                    account_to_allocate: entry_info.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            Entry::LEN as u64,
+        )?;
+        assign(
+            CpiContext::new(
+                system_program,
+                Assign {
+                    account_to_assign: entry_info.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            ctx.program_id,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveEntries<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ENTRY_OPERATOR)
+            @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind == STORAGE_DIRECT_PDA @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+pub fn remove_entries<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RemoveEntries<'info>>,
+    subjects: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        subjects.len() == ctx.remaining_accounts.len(),
+        BouncerError::BatchLengthMismatch
+    );
+
+    let list_key = ctx.accounts.list.key();
+    let refund_to = ctx.accounts.refund_to.to_account_info();
+
+    for (subject, entry_info) in subjects.iter().zip(ctx.remaining_accounts.iter()) {
+        let (expected_entry, _) = Pubkey::find_program_address(
+            &[b"entry", list_key.as_ref(), subject.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(entry_info.key(), expected_entry, BouncerError::EntryPdaMismatch);
+        require_keys_eq!(
+            *entry_info.owner,
+            *ctx.program_id,
+            BouncerError::EntryAccountInvalidOwner
+        );
+
+        {
+            let data = entry_info.try_borrow_data()?;
+            let mut data_slice: &[u8] = &data;
+            let entry = Entry::try_deserialize(&mut data_slice)
+                .map_err(|_| error!(BouncerError::EntryDataMismatch))?;
+            require_keys_eq!(entry.list, list_key, BouncerError::EntryDataMismatch);
+            require_keys_eq!(entry.subject, *subject, BouncerError::EntryDataMismatch);
+        }
+
+        // Same as Anchor's `close`: drain lamports, hand back to the system program
+        let lamports = entry_info.lamports();
+        **refund_to.lamports.borrow_mut() = refund_to
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **entry_info.lamports.borrow_mut() = 0;
+        entry_info.assign(&System::id());
+        entry_info.resize(0)?;
+
+        emit!(EntryRemoved {
+            list: list_key,
+            subject: *subject
+        });
+    }
+
+    let list = &mut ctx.accounts.list;
+    list.entry_count = list.entry_count.saturating_sub(subjects.len() as u32);
+    list.bump_revision();
+
+    Ok(())
+}
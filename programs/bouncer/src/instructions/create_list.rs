@@ -3,8 +3,8 @@ use anchor_lang::prelude::*;
 use crate::{
     errors::BouncerError,
     state::{
-        List, ListCreated, POLICY_ALLOWLIST, POLICY_BLOCKLIST, STORAGE_DIRECT_PDA,
-        STORAGE_MERKLE_ROOT, LIST_VERSION,
+        List, ListCreated, FLAG_ROOT_HISTORY, POLICY_ALLOWLIST, POLICY_BLOCKLIST,
+        STORAGE_COMPRESSED_TREE, STORAGE_DIRECT_PDA, STORAGE_MERKLE_ROOT, LIST_VERSION,
     },
 };
 
@@ -40,7 +40,9 @@ pub fn create_list(
         BouncerError::InvalidPolicy
     );
     require!(
-        storage_kind == STORAGE_DIRECT_PDA || storage_kind == STORAGE_MERKLE_ROOT,
+        storage_kind == STORAGE_DIRECT_PDA
+            || storage_kind == STORAGE_MERKLE_ROOT
+            || storage_kind == STORAGE_COMPRESSED_TREE,
         BouncerError::InvalidStorageKind
     );
 
@@ -52,10 +54,14 @@ pub fn create_list(
     list.list_id = list_id;
     list.policy = policy;
     list.storage_kind = storage_kind;
-    list.flags = flags;
+    // FLAG_ROOT_HISTORY tracks an account, only configure_root_history sets it
+    list.flags = flags & !FLAG_ROOT_HISTORY;
     list.entry_count = 0;
+    list.delegate_count = 0;
     list.reserved0 = 0;
     list.storage_config = [0u8; 96];
+    list.pending_authority = Pubkey::default();
+    list.revision = 0;
 
     emit!(ListCreated {
         list: list.key(),
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::BouncerError,
+    state::{Delegate, DelegateAdded, DelegateRevoked, List, DELEGATE_VERSION, PERM_ALL},
+};
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct AddDelegate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Delegate::LEN,
+        seeds = [b"delegate", list.key().as_ref(), operator.as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_delegate(ctx: Context<AddDelegate>, operator: Pubkey, permissions: u16) -> Result<()> {
+    require!(
+        permissions != 0 && (permissions & !PERM_ALL) == 0,
+        BouncerError::InvalidPermissions
+    );
+
+    let list = &mut ctx.accounts.list;
+    let delegate = &mut ctx.accounts.delegate;
+
+    // Re-adding an existing delegate replaces its permissions
+    if delegate.version == 0 {
+        list.delegate_count = list.delegate_count.saturating_add(1);
+        delegate.version = DELEGATE_VERSION;
+        delegate.bump = ctx.bumps.delegate;
+        delegate.list = list.key();
+        delegate.operator = operator;
+    } else {
+        require_keys_eq!(delegate.list, list.key(), BouncerError::EntryDataMismatch);
+        require_keys_eq!(delegate.operator, operator, BouncerError::EntryDataMismatch);
+    }
+
+    delegate.permissions = permissions;
+
+    emit!(DelegateAdded {
+        list: list.key(),
+        operator,
+        permissions,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct RevokeDelegate<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ BouncerError::Unauthorized,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        seeds = [b"delegate", list.key().as_ref(), operator.as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+pub fn revoke_delegate(ctx: Context<RevokeDelegate>, operator: Pubkey) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    list.delegate_count = list.delegate_count.saturating_sub(1);
+
+    emit!(DelegateRevoked {
+        list: ctx.accounts.list.key(),
+        operator,
+    });
+    Ok(())
+}
@@ -3,8 +3,8 @@ use anchor_lang::prelude::*;
 use crate::{
     errors::BouncerError,
     state::{
-        Entry, EntryRemoved, EntryUpserted, List, ENTRY_STATUS_ALLOW, ENTRY_STATUS_BLOCK,
-        STORAGE_DIRECT_PDA, ENTRY_VERSION,
+        Delegate, Entry, EntryRemoved, EntryUpserted, List, ENTRY_STATUS_ALLOW,
+        ENTRY_STATUS_BLOCK, ENTRY_VERSION, PERM_ENTRY_OPERATOR, STORAGE_DIRECT_PDA,
     },
 };
 
@@ -18,7 +18,8 @@ pub struct UpsertEntry<'info> {
 
     #[account(
         mut,
-        has_one = authority @ BouncerError::Unauthorized,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ENTRY_OPERATOR)
+            @ BouncerError::Unauthorized,
         constraint = !list.is_frozen() @ BouncerError::Frozen,
         constraint = list.storage_kind == STORAGE_DIRECT_PDA @ BouncerError::InvalidStorageKind,
         seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
@@ -26,6 +27,12 @@ pub struct UpsertEntry<'info> {
     )]
     pub list: Account<'info, List>,
 
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
     #[account(
         init_if_needed,
         payer = payer,
@@ -38,7 +45,13 @@ pub struct UpsertEntry<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn upsert_entry(ctx: Context<UpsertEntry>, subject: Pubkey, status: u8) -> Result<()> {
+pub fn upsert_entry(
+    ctx: Context<UpsertEntry>,
+    subject: Pubkey,
+    status: u8,
+    valid_from: Option<i64>,
+    valid_until: Option<i64>,
+) -> Result<()> {
     require!(
         status == ENTRY_STATUS_ALLOW || status == ENTRY_STATUS_BLOCK,
         BouncerError::InvalidStatus
@@ -60,17 +73,37 @@ pub fn upsert_entry(ctx: Context<UpsertEntry>, subject: Pubkey, status: u8) -> R
         require_keys_eq!(entry.subject, subject, BouncerError::EntryDataMismatch);
     }
 
+    // An omitted bound keeps the stored one (unbounded for a new entry)
     entry.status = status;
+    entry.valid_from = valid_from.unwrap_or(entry.valid_from);
+    entry.valid_until = valid_until.unwrap_or(entry.valid_until);
+    check_validity_window(entry.valid_from, entry.valid_until)?;
+    list.bump_revision();
 
     emit!(EntryUpserted {
         list: list.key(),
         subject,
-        status
+        status,
+        valid_from: entry.valid_from,
+        valid_until: entry.valid_until,
     });
 
     Ok(())
 }
 
+// `[valid_from, valid_until)` with 0 meaning unbounded on that side
+pub(crate) fn check_validity_window(valid_from: i64, valid_until: i64) -> Result<()> {
+    require!(
+        valid_from >= 0 && valid_until >= 0,
+        BouncerError::InvalidValidityWindow
+    );
+    require!(
+        valid_until == 0 || valid_until > valid_from,
+        BouncerError::InvalidValidityWindow
+    );
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(subject: Pubkey)]
 pub struct RemoveEntry<'info> {
@@ -78,7 +111,8 @@ pub struct RemoveEntry<'info> {
 
     #[account(
         mut,
-        has_one = authority @ BouncerError::Unauthorized,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ENTRY_OPERATOR)
+            @ BouncerError::Unauthorized,
         constraint = !list.is_frozen() @ BouncerError::Frozen,
         constraint = list.storage_kind == STORAGE_DIRECT_PDA @ BouncerError::InvalidStorageKind,
         seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
@@ -86,6 +120,12 @@ pub struct RemoveEntry<'info> {
     )]
     pub list: Account<'info, List>,
 
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
     #[account(
         mut,
         close = refund_to,
@@ -107,6 +147,7 @@ pub fn remove_entry(ctx: Context<RemoveEntry>, subject: Pubkey) -> Result<()> {
     require_keys_eq!(entry.subject, subject, BouncerError::EntryDataMismatch);
 
     list.entry_count = list.entry_count.saturating_sub(1);
+    list.bump_revision();
 
     emit!(EntryRemoved {
         list: list.key(),
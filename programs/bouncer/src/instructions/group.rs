@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::BouncerError,
+    instructions::is_allowed,
+    state::{
+        GroupClosed, GroupSet, LeafData, List, ListGroup, COMBINATOR_AND, COMBINATOR_OR,
+        GROUP_VERSION, MAX_GROUP_LISTS,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(group_id: u64)]
+pub struct CreateGroup<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ListGroup::LEN,
+        seeds = [b"group", payer.key().as_ref(), &group_id.to_le_bytes()],
+        bump
+    )]
+    pub group: Account<'info, ListGroup>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Member lists are passed in remaining_accounts, in the same order as `lists`,
+// so that each one can be checked to be a real bouncer List.
+pub fn create_group(
+    ctx: Context<CreateGroup>,
+    group_id: u64,
+    combinator: u8,
+    lists: Vec<Pubkey>,
+) -> Result<()> {
+    validate_members(ctx.program_id, combinator, &lists, ctx.remaining_accounts)?;
+
+    let group = &mut ctx.accounts.group;
+    group.version = GROUP_VERSION;
+    group.bump = ctx.bumps.group;
+    group.authority = ctx.accounts.authority.key();
+    group.creator = ctx.accounts.payer.key();
+    group.group_id = group_id;
+    write_members(group, combinator, &lists);
+
+    emit!(GroupSet {
+        group: group.key(),
+        authority: group.authority,
+        combinator,
+        lists,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetGroup<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ BouncerError::Unauthorized,
+        seeds = [b"group", group.creator.as_ref(), &group.group_id.to_le_bytes()],
+        bump = group.bump
+    )]
+    pub group: Account<'info, ListGroup>,
+}
+
+pub fn set_group(ctx: Context<SetGroup>, combinator: u8, lists: Vec<Pubkey>) -> Result<()> {
+    validate_members(ctx.program_id, combinator, &lists, ctx.remaining_accounts)?;
+
+    let group = &mut ctx.accounts.group;
+    write_members(group, combinator, &lists);
+
+    emit!(GroupSet {
+        group: group.key(),
+        authority: group.authority,
+        combinator,
+        lists,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseGroup<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        has_one = authority @ BouncerError::Unauthorized,
+        seeds = [b"group", group.creator.as_ref(), &group.group_id.to_le_bytes()],
+        bump = group.bump
+    )]
+    pub group: Account<'info, ListGroup>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+pub fn close_group(ctx: Context<CloseGroup>) -> Result<()> {
+    emit!(GroupClosed {
+        group: ctx.accounts.group.key(),
+        authority: ctx.accounts.authority.key(),
+    });
+    Ok(())
+}
+
+fn validate_members(
+    program_id: &Pubkey,
+    combinator: u8,
+    lists: &[Pubkey],
+    list_infos: &[AccountInfo],
+) -> Result<()> {
+    require!(
+        combinator == COMBINATOR_AND || combinator == COMBINATOR_OR,
+        BouncerError::InvalidCombinator
+    );
+    require!(
+        !lists.is_empty() && lists.len() <= MAX_GROUP_LISTS && lists.len() == list_infos.len(),
+        BouncerError::InvalidGroupMembers
+    );
+
+    for (i, (key, info)) in lists.iter().zip(list_infos.iter()).enumerate() {
+        require!(!lists[..i].contains(key), BouncerError::InvalidGroupMembers);
+        load_member(program_id, key, info)?;
+    }
+
+    Ok(())
+}
+
+fn write_members(group: &mut ListGroup, combinator: u8, lists: &[Pubkey]) {
+    group.combinator = combinator;
+    group.list_count = lists.len() as u8;
+    group.lists = [Pubkey::default(); MAX_GROUP_LISTS];
+    group.lists[..lists.len()].copy_from_slice(lists);
+}
+
+fn load_member(program_id: &Pubkey, expected: &Pubkey, info: &AccountInfo) -> Result<List> {
+    require_keys_eq!(info.key(), *expected, BouncerError::InvalidGroupMembers);
+    require_keys_eq!(*info.owner, *program_id, BouncerError::InvalidGroupMembers);
+    let data = info.try_borrow_data()?;
+    let mut data_slice: &[u8] = &data;
+    List::try_deserialize(&mut data_slice).map_err(|_| error!(BouncerError::InvalidGroupMembers))
+}
+
+#[derive(Accounts)]
+pub struct AssertAllowedGroup<'info> {
+    #[account(
+        seeds = [b"group", group.creator.as_ref(), &group.group_id.to_le_bytes()],
+        bump = group.bump
+    )]
+    pub group: Account<'info, ListGroup>,
+}
+
+// remaining_accounts holds one (list, aux) pair per member list, in group
+// order. The aux slot is what the member's storage kind needs:
+// - Direct-PDA: the subject's Entry PDA ["entry", list, subject];
+// - Merkle root: the list's RootHistory ["roots", list] to accept a recent
+//   root, or the subject's Receipt when its proof is empty; anything else
+//   limits the proof to the current root;
+// - compressed tree: the tree account ["tree", list], which is required.
+// `proofs` and `leaves` hold one proof and leaf metadata per member (empty
+// and None for Direct-PDA lists).
+pub fn assert_allowed_group(
+    ctx: Context<AssertAllowedGroup>,
+    subject: Pubkey,
+    proofs: Vec<Vec<u8>>,
+    leaves: Vec<Option<LeafData>>,
+) -> Result<()> {
+    let group = &ctx.accounts.group;
+    let members = group.members();
+    require!(
+        proofs.len() == members.len()
+            && leaves.len() == members.len()
+            && ctx.remaining_accounts.len() == members.len() * 2,
+        BouncerError::InvalidGroupMembers
+    );
+
+    let mut any_allowed = false;
+    for (i, (list_key, (proof, leaf))) in members.iter().zip(proofs.iter().zip(&leaves)).enumerate()
+    {
+        let list_info = &ctx.remaining_accounts[i * 2];
+        let entry_info = &ctx.remaining_accounts[i * 2 + 1];
+        let list = load_member(ctx.program_id, list_key, list_info)?;
+
+        let result = is_allowed(
+            ctx.program_id,
+            list_key,
+            &list,
+            subject,
+            proof,
+            leaf.as_ref(),
+            std::slice::from_ref(entry_info),
+        );
+
+        match group.combinator {
+            // Every member must pass; any failure is reported as-is
+            COMBINATOR_AND => require!(result?, BouncerError::NotAllowed),
+            // One passing member is enough; members that fail to verify just don't count
+            COMBINATOR_OR => {
+                if result.unwrap_or(false) {
+                    any_allowed = true;
+                    break;
+                }
+            }
+            _ => return err!(BouncerError::InvalidCombinator),
+        }
+    }
+
+    if group.combinator == COMBINATOR_OR {
+        require!(any_allowed, BouncerError::NotAllowed);
+    }
+
+    Ok(())
+}
@@ -2,7 +2,11 @@ use anchor_lang::prelude::*;
 
 use crate::{
     errors::BouncerError,
-    state::{List, MerkleConfigSet, MERKLE_MAX_DEPTH, STORAGE_MERKLE_ROOT},
+    state::{
+        Delegate, List, MerkleConfigSet, MinTierSet, RootHistory, RootHistoryClosed,
+        RootHistoryConfigured, FLAG_ROOT_HISTORY, MERKLE_MAX_DEPTH, PERM_ADMIN,
+        PERM_MERKLE_PUBLISHER, ROOT_HISTORY_VERSION, STORAGE_COMPRESSED_TREE, STORAGE_MERKLE_ROOT,
+    },
 };
 
 #[derive(Accounts)]
@@ -11,13 +15,29 @@ pub struct SetMerkleConfig<'info> {
 
     #[account(
         mut,
-        has_one = authority @ BouncerError::Unauthorized,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_MERKLE_PUBLISHER)
+            @ BouncerError::Unauthorized,
         constraint = !list.is_frozen() @ BouncerError::Frozen,
         constraint = list.storage_kind == STORAGE_MERKLE_ROOT @ BouncerError::InvalidStorageKind,
         seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
         bump = list.bump
     )]
     pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    // Where the root being replaced is kept for in-flight proofs; required
+    // once configure_root_history has run
+    #[account(
+        mut,
+        seeds = [b"roots", list.key().as_ref()],
+        bump = root_history.bump
+    )]
+    pub root_history: Option<Account<'info, RootHistory>>,
 }
 
 pub fn set_merkle_config(
@@ -31,7 +51,21 @@ pub fn set_merkle_config(
     );
 
     let list = &mut ctx.accounts.list;
+    let old_depth = list.merkle_depth();
+    let old_root = list.merkle_root();
+
+    require!(
+        !list.has_root_history() || ctx.accounts.root_history.is_some(),
+        BouncerError::MissingRootHistory
+    );
+    if let Some(root_history) = ctx.accounts.root_history.as_mut() {
+        if old_depth != 0 && (old_depth != depth || old_root != root) {
+            root_history.push(old_root, old_depth, Clock::get()?.slot);
+        }
+    }
+
     list.set_merkle_config(depth, root);
+    list.bump_revision();
 
     emit!(MerkleConfigSet {
         list: list.key(),
@@ -43,3 +77,148 @@ pub fn set_merkle_config(
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct ConfigureRootHistory<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ADMIN)
+            @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind == STORAGE_MERKLE_ROOT @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RootHistory::LEN,
+        seeds = [b"roots", list.key().as_ref()],
+        bump
+    )]
+    pub root_history: Account<'info, RootHistory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the list's RootHistory, or change its max age on an existing one.
+/// The max age is required: without one an old root keeps proving a removed
+/// allowlist member present, or a newly blocked subject absent.
+pub fn configure_root_history(
+    ctx: Context<ConfigureRootHistory>,
+    max_age_slots: u64,
+) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    require!(max_age_slots > 0, BouncerError::RootHistoryMaxAgeRequired);
+    list.flags |= FLAG_ROOT_HISTORY;
+    list.bump_revision();
+
+    let root_history = &mut ctx.accounts.root_history;
+
+    if root_history.version == 0 {
+        root_history.version = ROOT_HISTORY_VERSION;
+        root_history.bump = ctx.bumps.root_history;
+        root_history.list = list.key();
+        root_history.depth = list.merkle_depth();
+    }
+
+    root_history.max_age_slots = max_age_slots;
+
+    emit!(RootHistoryConfigured {
+        list: list.key(),
+        authority: ctx.accounts.authority.key(),
+        max_age_slots,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseRootHistory<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        has_one = list @ BouncerError::InvalidMerkleConfig,
+        seeds = [b"roots", list.key().as_ref()],
+        bump = root_history.bump
+    )]
+    pub root_history: Account<'info, RootHistory>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+/// Drop the list's RootHistory: only the current root verifies afterwards.
+pub fn close_root_history(ctx: Context<CloseRootHistory>) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    list.flags &= !FLAG_ROOT_HISTORY;
+    list.bump_revision();
+    emit!(RootHistoryClosed {
+        list: list.key(),
+        authority: ctx.accounts.authority.key(),
+        refund_to: ctx.accounts.refund_to.key(),
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMinTier<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ADMIN)
+            @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind == STORAGE_MERKLE_ROOT
+            || list.storage_kind == STORAGE_COMPRESSED_TREE @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+}
+
+/// Minimum leaf tier a Merkle allowlist requires (0 = any).
+pub fn set_min_tier(ctx: Context<SetMinTier>, min_tier: u16) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    list.set_merkle_min_tier(min_tier);
+    list.bump_revision();
+
+    emit!(MinTierSet {
+        list: list.key(),
+        authority: ctx.accounts.authority.key(),
+        min_tier,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,23 @@
 pub mod admin;
 pub mod assert;
+pub mod batch;
 pub mod create_list;
+pub mod delegate;
 pub mod entry;
+pub mod group;
 pub mod merkle;
+pub mod proof_buffer;
+pub mod receipt;
+pub mod tree;
 
 pub use admin::*;
 pub use assert::*;
+pub use batch::*;
 pub use create_list::*;
+pub use delegate::*;
 pub use entry::*;
+pub use group::*;
 pub use merkle::*;
+pub use proof_buffer::*;
+pub use receipt::*;
+pub use tree::*;
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::BouncerError,
+    state::{
+        LeafData, List, ProofBuffer, PROOF_BUFFER_MAX_LEN, PROOF_BUFFER_VERSION,
+        STORAGE_COMPRESSED_TREE, STORAGE_MERKLE_ROOT,
+    },
+};
+
+#[derive(Accounts)]
+pub struct WriteProofBuffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub subject: Signer<'info>,
+
+    #[account(
+        constraint = list.storage_kind == STORAGE_MERKLE_ROOT
+            || list.storage_kind == STORAGE_COMPRESSED_TREE @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProofBuffer::LEN,
+        seeds = [b"proof", list.key().as_ref(), subject.key().as_ref()],
+        bump
+    )]
+    pub proof_buffer: Account<'info, ProofBuffer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Store (or replace) the signing subject's proof for `list`.
+pub fn write_proof_buffer(
+    ctx: Context<WriteProofBuffer>,
+    proof: Vec<u8>,
+    leaf: Option<LeafData>,
+) -> Result<()> {
+    require!(
+        proof.len() <= PROOF_BUFFER_MAX_LEN,
+        BouncerError::InvalidProofLength
+    );
+
+    let proof_buffer = &mut ctx.accounts.proof_buffer;
+    if proof_buffer.version == 0 {
+        proof_buffer.version = PROOF_BUFFER_VERSION;
+        proof_buffer.bump = ctx.bumps.proof_buffer;
+        proof_buffer.list = ctx.accounts.list.key();
+        proof_buffer.subject = ctx.accounts.subject.key();
+    }
+
+    proof_buffer.leaf = leaf;
+    proof_buffer.proof = proof;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseProofBuffer<'info> {
+    pub subject: Signer<'info>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        has_one = subject @ BouncerError::Unauthorized,
+        seeds = [b"proof", proof_buffer.list.as_ref(), subject.key().as_ref()],
+        bump = proof_buffer.bump
+    )]
+    pub proof_buffer: Account<'info, ProofBuffer>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+pub fn close_proof_buffer(_ctx: Context<CloseProofBuffer>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::BouncerError,
+    instructions::{claimed_leaf, proves_leaf, receipt_is_current},
+    merkle::{compute_sparse_merkle_root_for_leaf, key_hash},
+    state::{
+        LeafData, List, Receipt, RECEIPT_VERSION, STORAGE_COMPRESSED_TREE, STORAGE_MERKLE_ROOT,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct ProveMembership<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = list.storage_kind == STORAGE_MERKLE_ROOT
+            || list.storage_kind == STORAGE_COMPRESSED_TREE @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Receipt::LEN,
+        seeds = [b"receipt", list.key().as_ref(), subject.as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, Receipt>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Verify `proof` for `subject` once and record the result in its Receipt.
+/// Remaining accounts are the list's RootHistory or tree account, as for
+/// `assert_allowed`. Re-proving refreshes the receipt to the newer root.
+pub fn prove_membership(
+    ctx: Context<ProveMembership>,
+    subject: Pubkey,
+    proof: Vec<u8>,
+    leaf: Option<LeafData>,
+) -> Result<()> {
+    let list = &ctx.accounts.list;
+    let key_hash = key_hash(&subject.to_bytes());
+    let leaf_hash = claimed_leaf(list, &key_hash, leaf.as_ref())?;
+    require!(
+        proves_leaf(
+            ctx.program_id,
+            &list.key(),
+            list,
+            key_hash,
+            leaf_hash,
+            &proof,
+            ctx.remaining_accounts,
+        )?,
+        BouncerError::InvalidMerkleProof
+    );
+    let root =
+        compute_sparse_merkle_root_for_leaf(list.merkle_depth(), key_hash, leaf_hash, &proof)?;
+
+    let receipt = &mut ctx.accounts.receipt;
+    if receipt.version == 0 {
+        receipt.version = RECEIPT_VERSION;
+        receipt.bump = ctx.bumps.receipt;
+        receipt.list = list.key();
+        receipt.subject = subject;
+        receipt.payer = ctx.accounts.payer.key();
+    }
+
+    receipt.root = root;
+    receipt.leaf_hash = leaf_hash;
+    receipt.leaf = leaf;
+    receipt.verified_slot = Clock::get()?.slot;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseReceipt<'info> {
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"receipt", receipt.list.as_ref(), receipt.subject.as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, Receipt>,
+
+    /// CHECK: whoever funded the receipt; receives the rent
+    #[account(mut, address = receipt.payer @ BouncerError::Unauthorized)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// CHECK: the receipt's list, which may already be closed
+    #[account(address = receipt.list @ BouncerError::InvalidReceipt)]
+    pub list: UncheckedAccount<'info>,
+}
+
+/// The payer may close its receipt at any time; anyone else only once it no
+/// longer verifies. Remaining accounts are as for `prove_membership`.
+pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+    if ctx.accounts.closer.key() == ctx.accounts.receipt.payer {
+        return Ok(());
+    }
+
+    let list_info = &ctx.accounts.list;
+    if list_info.owner != ctx.program_id || list_info.data_is_empty() {
+        // List is gone, so the receipt can never verify again
+        return Ok(());
+    }
+
+    let data = list_info.try_borrow_data()?;
+    let mut data_slice: &[u8] = &data;
+    let list = List::try_deserialize(&mut data_slice)?;
+    let current = receipt_is_current(
+        ctx.program_id,
+        &list_info.key(),
+        &list,
+        &ctx.accounts.receipt,
+        ctx.remaining_accounts,
+    )?;
+    require!(!current, BouncerError::ReceiptStillValid);
+
+    Ok(())
+}
@@ -0,0 +1,493 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::BouncerError,
+    merkle::{
+        compute_sparse_merkle_path_for_leaf, compute_sparse_merkle_root_for_leaf, empty_root,
+        expand_proof, get_bit_le, key_hash, leaf_node,
+    },
+    state::{
+        CompressedTree, Delegate, LeafData, List, TreeClosed, TreeLeaf, TreeLeafUpdated,
+        PERM_ENTRY_OPERATOR, STORAGE_COMPRESSED_TREE, TREE_CHANGELOG_LEN, TREE_MAX_DEPTH,
+    },
+};
+
+#[derive(Accounts)]
+pub struct InitCompressedTree<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind == STORAGE_COMPRESSED_TREE
+            @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = CompressedTree::LEN,
+        seeds = [b"tree", list.key().as_ref()],
+        bump
+    )]
+    pub tree: AccountLoader<'info, CompressedTree>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_compressed_tree(ctx: Context<InitCompressedTree>, depth: u8) -> Result<()> {
+    require!(
+        depth > 0 && depth <= TREE_MAX_DEPTH,
+        BouncerError::InvalidMerkleConfig
+    );
+
+    let root = empty_root(depth);
+    let list = &mut ctx.accounts.list;
+    list.set_merkle_config(depth, root);
+    list.bump_revision();
+
+    let mut tree = ctx.accounts.tree.load_init()?;
+    tree.list = list.key();
+    tree.depth = depth;
+    tree.bump = ctx.bumps.tree;
+    tree.sequence = 0;
+    tree.active_index = 0;
+    tree.changelog[0].root = root;
+
+    Ok(())
+}
+
+// Closing the tree empties the list: it has no root until a new tree is initialized
+#[derive(Accounts)]
+pub struct CloseTree<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        has_one = list @ BouncerError::InvalidMerkleConfig,
+        seeds = [b"tree", list.key().as_ref()],
+        bump
+    )]
+    pub tree: AccountLoader<'info, CompressedTree>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+pub fn close_tree(ctx: Context<CloseTree>) -> Result<()> {
+    let list = &mut ctx.accounts.list;
+    list.set_merkle_config(0, [0u8; 32]);
+    list.entry_count = 0;
+    list.bump_revision();
+
+    emit!(TreeClosed {
+        list: list.key(),
+        authority: ctx.accounts.authority.key(),
+        refund_to: ctx.accounts.refund_to.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreeLeaf<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = list.is_authorized(&authority.key(), delegate.as_deref(), PERM_ENTRY_OPERATOR)
+            @ BouncerError::Unauthorized,
+        constraint = !list.is_frozen() @ BouncerError::Frozen,
+        constraint = list.storage_kind == STORAGE_COMPRESSED_TREE
+            @ BouncerError::InvalidStorageKind,
+        seeds = [b"bouncer", list.creator.as_ref(), &list.list_id.to_le_bytes()],
+        bump = list.bump
+    )]
+    pub list: Account<'info, List>,
+
+    #[account(
+        seeds = [b"delegate", list.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    #[account(mut, has_one = list @ BouncerError::InvalidMerkleConfig)]
+    pub tree: AccountLoader<'info, CompressedTree>,
+}
+
+/// Insert `subject` (leaf 0 -> 1), committing to `leaf` when given. `root` is
+/// the root `proof` was built against and may be any root still in the changelog.
+pub fn append_leaf(
+    ctx: Context<UpdateTreeLeaf>,
+    subject: Pubkey,
+    root: [u8; 32],
+    proof: Vec<u8>,
+    leaf: Option<LeafData>,
+) -> Result<()> {
+    let old_leaf = TreeLeaf {
+        value: 0,
+        data: None,
+    };
+    let new_leaf = TreeLeaf {
+        value: 1,
+        data: leaf,
+    };
+    replace_leaf(ctx, subject, root, old_leaf, new_leaf, proof)
+}
+
+/// Set the leaf for `subject` from `old_leaf` to `new_leaf`. Present leaves may
+/// carry metadata, so a member's metadata can also be changed in place (1 -> 1).
+pub fn replace_leaf(
+    ctx: Context<UpdateTreeLeaf>,
+    subject: Pubkey,
+    root: [u8; 32],
+    old_leaf: TreeLeaf,
+    new_leaf: TreeLeaf,
+    proof: Vec<u8>,
+) -> Result<()> {
+    let key_hash = key_hash(&subject.to_bytes());
+    let old_node = leaf_node(&key_hash, old_leaf.value, old_leaf.data.as_ref())?;
+    let new_node = leaf_node(&key_hash, new_leaf.value, new_leaf.data.as_ref())?;
+    require!(old_node != new_node, BouncerError::InvalidStatus);
+
+    let mut tree = ctx.accounts.tree.load_mut()?;
+    apply_leaf_update(&mut tree, key_hash, root, old_node, new_node, &proof)?;
+
+    let new_root = tree.current_root();
+    let sequence = tree.sequence;
+    let list = &mut ctx.accounts.list;
+    list.set_merkle_config(tree.depth, new_root);
+    list.bump_revision();
+    if new_leaf.value > old_leaf.value {
+        list.entry_count = list.entry_count.saturating_add(1);
+    } else if new_leaf.value < old_leaf.value {
+        list.entry_count = list.entry_count.saturating_sub(1);
+    }
+
+    emit!(TreeLeafUpdated {
+        list: list.key(),
+        subject,
+        old_value: old_leaf.value,
+        new_value: new_leaf.value,
+        new_leaf: new_leaf.data,
+        root: new_root,
+        sequence,
+    });
+
+    Ok(())
+}
+
+/// Bring `proof` for `key_hash`, built against `root`, forward to the tree's
+/// current root by replaying every change made since. Fails if `root` has left
+/// the changelog or a later change touched the same key.
+pub fn fast_forward_proof(
+    tree: &CompressedTree,
+    key_hash: &[u8; 32],
+    root: &[u8; 32],
+    proof: &[u8],
+) -> Result<Vec<u8>> {
+    let depth = tree.depth as usize;
+    let slots: Vec<usize> = tree.live_slots().collect();
+    let start = slots
+        .iter()
+        .rposition(|&slot| tree.changelog[slot].root == *root)
+        .ok_or(BouncerError::StaleRoot)?;
+
+    // Two leaves' paths meet above the highest key bit where they differ; the
+    // other change's node at that level is our sibling there.
+    let mut proof = expand_proof(tree.depth, proof)?.into_owned();
+    for &slot in &slots[start + 1..] {
+        let change = &tree.changelog[slot];
+        let critical = (0..depth)
+            .rev()
+            .find(|&i| get_bit_le(key_hash, i) != get_bit_le(&change.key_hash, i))
+            .ok_or(BouncerError::LeafConcurrentlyModified)?;
+        proof[critical * 32..(critical + 1) * 32].copy_from_slice(&change.path[critical]);
+    }
+
+    Ok(proof)
+}
+
+/// Whether `proof` shows `leaf` at `key_hash` in the tree as it is now. The
+/// proof may target any root still in the changelog, provided no later change
+/// touched the same key; a proof that does not verify yields `false`.
+pub fn tree_proves_leaf(
+    tree: &CompressedTree,
+    key_hash: [u8; 32],
+    leaf: [u8; 32],
+    proof: &[u8],
+) -> Result<bool> {
+    let root = compute_sparse_merkle_root_for_leaf(tree.depth, key_hash, leaf, proof)?;
+    let proof = match fast_forward_proof(tree, &key_hash, &root, proof) {
+        Ok(proof) => proof,
+        // The root left the changelog, or the leaf changed after it
+        Err(_) => return Ok(false),
+    };
+    let current = compute_sparse_merkle_root_for_leaf(tree.depth, key_hash, leaf, &proof)?;
+    Ok(current == tree.current_root())
+}
+
+// Replace the leaf node `old_leaf` for `key_hash` with `new_leaf`
+fn apply_leaf_update(
+    tree: &mut CompressedTree,
+    key_hash: [u8; 32],
+    root: [u8; 32],
+    old_leaf: [u8; 32],
+    new_leaf: [u8; 32],
+    proof: &[u8],
+) -> Result<()> {
+    let depth = tree.depth as usize;
+
+    // The proof must hold against the root it claims to be built for
+    let claimed = compute_sparse_merkle_root_for_leaf(tree.depth, key_hash, old_leaf, proof)?;
+    require!(claimed == root, BouncerError::InvalidMerkleProof);
+
+    let proof = fast_forward_proof(tree, &key_hash, &root, proof)?;
+    let current = compute_sparse_merkle_root_for_leaf(tree.depth, key_hash, old_leaf, &proof)?;
+    require!(
+        current == tree.current_root(),
+        BouncerError::InvalidMerkleProof
+    );
+
+    let path = compute_sparse_merkle_path_for_leaf(tree.depth, key_hash, new_leaf, &proof)?;
+    let next = (tree.active_index as usize + 1) % TREE_CHANGELOG_LEN;
+    let entry = &mut tree.changelog[next];
+    entry.root = path[depth];
+    entry.key_hash = key_hash;
+    for (level, node) in entry.path.iter_mut().enumerate() {
+        *node = if level < depth { path[level] } else { [0u8; 32] };
+    }
+
+    tree.active_index = next as u64;
+    tree.sequence = tree.sequence.saturating_add(1);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::leaf_hash;
+    use anchor_lang::error::Error;
+    use bouncer_merkle::SparseMerkleTree;
+
+    const DEPTH: u8 = 16;
+
+    fn subject(i: u8) -> [u8; 32] {
+        [i; 32]
+    }
+
+    fn new_tree() -> Box<CompressedTree> {
+        let mut tree: Box<CompressedTree> = Box::new(bytemuck::Zeroable::zeroed());
+        tree.depth = DEPTH;
+        tree.changelog[0].root = empty_root(DEPTH);
+        tree
+    }
+
+    fn insert(
+        tree: &mut CompressedTree,
+        snapshot: &SparseMerkleTree,
+        i: u8,
+    ) -> Result<()> {
+        apply_leaf_update(
+            tree,
+            key_hash(&subject(i)),
+            snapshot.root(),
+            [0u8; 32],
+            leaf_hash(&key_hash(&subject(i)), 1),
+            &snapshot.proof(&subject(i)),
+        )
+    }
+
+    fn assert_bouncer_error(result: Result<()>, expected: BouncerError) {
+        match result {
+            Err(Error::AnchorError(err)) => {
+                assert_eq!(err.error_code_number, u32::from(expected))
+            }
+            other => panic!("expected {expected:?}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn concurrent_appends_against_one_root() {
+        let mut tree = new_tree();
+        let snapshot = SparseMerkleTree::new(DEPTH).unwrap();
+        let mut expected = SparseMerkleTree::new(DEPTH).unwrap();
+
+        // Every proof was built before any of the updates landed
+        for i in 1..TREE_CHANGELOG_LEN as u8 {
+            insert(&mut tree, &snapshot, i).unwrap();
+            expected.insert(&subject(i)).unwrap();
+            assert_eq!(tree.current_root(), expected.root());
+        }
+        assert_eq!(tree.sequence, TREE_CHANGELOG_LEN as u64 - 1);
+    }
+
+    #[test]
+    fn updates_against_staggered_roots() {
+        let mut tree = new_tree();
+        let mut snapshots = vec![SparseMerkleTree::new(DEPTH).unwrap()];
+        let mut expected = SparseMerkleTree::new(DEPTH).unwrap();
+
+        // Each proof lags a different number of updates behind the tree
+        for i in 1..=20u8 {
+            let lag = (i as usize % TREE_CHANGELOG_LEN).min(snapshots.len() - 1);
+            let snapshot = &snapshots[snapshots.len() - 1 - lag];
+            insert(&mut tree, snapshot, i).unwrap();
+
+            expected.insert(&subject(i)).unwrap();
+            assert_eq!(tree.current_root(), expected.root());
+            snapshots.push(expected.clone());
+        }
+
+        // Removing a leaf fast-forwards the same way
+        let snapshot = &snapshots[snapshots.len() - 4];
+        apply_leaf_update(
+            &mut tree,
+            key_hash(&subject(3)),
+            snapshot.root(),
+            leaf_hash(&key_hash(&subject(3)), 1),
+            [0u8; 32],
+            &snapshot.proof(&subject(3)),
+        )
+        .unwrap();
+        expected.remove(&subject(3));
+        assert_eq!(tree.current_root(), expected.root());
+    }
+
+    #[test]
+    fn removed_leaf_no_longer_proves_against_old_root() {
+        let mut tree = new_tree();
+        let mut expected = SparseMerkleTree::new(DEPTH).unwrap();
+        for i in 1..=3u8 {
+            insert(&mut tree, &expected.clone(), i).unwrap();
+            expected.insert(&subject(i)).unwrap();
+        }
+        let old = expected.clone();
+        let present = |i: u8| leaf_hash(&key_hash(&subject(i)), 1);
+
+        apply_leaf_update(
+            &mut tree,
+            key_hash(&subject(2)),
+            old.root(),
+            present(2),
+            [0u8; 32],
+            &old.proof(&subject(2)),
+        )
+        .unwrap();
+        expected.remove(&subject(2));
+
+        // The old root is still in the changelog, but the removal is replayed
+        let key = key_hash(&subject(2));
+        assert!(!tree_proves_leaf(&tree, key, present(2), &old.proof(&subject(2))).unwrap());
+        assert!(!tree.leaf_unchanged_since(&old.root(), &key));
+        assert!(tree_proves_leaf(&tree, key, [0u8; 32], &expected.proof(&subject(2))).unwrap());
+
+        // Other leaves' old proofs are fast-forwarded and still hold
+        let key = key_hash(&subject(1));
+        assert!(tree_proves_leaf(&tree, key, present(1), &old.proof(&subject(1))).unwrap());
+        assert!(tree.leaf_unchanged_since(&old.root(), &key));
+    }
+
+    #[test]
+    fn rejects_root_evicted_from_changelog() {
+        let mut tree = new_tree();
+        let stale = SparseMerkleTree::new(DEPTH).unwrap();
+        let mut current = stale.clone();
+
+        for i in 1..=TREE_CHANGELOG_LEN as u8 {
+            insert(&mut tree, &current, i).unwrap();
+            current.insert(&subject(i)).unwrap();
+        }
+
+        assert_bouncer_error(insert(&mut tree, &stale, 100), BouncerError::StaleRoot);
+        insert(&mut tree, &current, 100).unwrap();
+    }
+
+    #[test]
+    fn rejects_concurrent_update_of_the_same_leaf() {
+        let mut tree = new_tree();
+        let snapshot = SparseMerkleTree::new(DEPTH).unwrap();
+
+        insert(&mut tree, &snapshot, 1).unwrap();
+        assert_bouncer_error(
+            insert(&mut tree, &snapshot, 1),
+            BouncerError::LeafConcurrentlyModified,
+        );
+    }
+
+    #[test]
+    fn rejects_proof_that_does_not_match_its_root() {
+        let mut tree = new_tree();
+        let mut other = SparseMerkleTree::new(DEPTH).unwrap();
+        other.insert(&subject(9)).unwrap();
+
+        let result = apply_leaf_update(
+            &mut tree,
+            key_hash(&subject(1)),
+            empty_root(DEPTH),
+            [0u8; 32],
+            leaf_hash(&key_hash(&subject(1)), 1),
+            &other.proof(&subject(1)),
+        );
+        assert_bouncer_error(result, BouncerError::InvalidMerkleProof);
+    }
+
+    #[test]
+    fn replaces_plain_leaf_with_metadata_leaf() {
+        let mut tree = new_tree();
+        let mut expected = SparseMerkleTree::new(DEPTH).unwrap();
+        for i in 1..=3u8 {
+            insert(&mut tree, &expected.clone(), i).unwrap();
+            expected.insert(&subject(i)).unwrap();
+        }
+
+        let key = key_hash(&subject(2));
+        let data = LeafData {
+            status: 1,
+            valid_until: 1_900_000_000,
+            tier: 2,
+        };
+        let old_node = leaf_node(&key, 1, None).unwrap();
+        let new_node = leaf_node(&key, 1, Some(&data)).unwrap();
+        apply_leaf_update(
+            &mut tree,
+            key,
+            expected.root(),
+            old_node,
+            new_node,
+            &expected.proof(&subject(2)),
+        )
+        .unwrap();
+
+        expected
+            .insert_with_data(
+                &subject(2),
+                &bouncer_merkle::LeafData {
+                    status: data.status,
+                    valid_until: data.valid_until,
+                    tier: data.tier,
+                },
+            )
+            .unwrap();
+        assert_eq!(tree.current_root(), expected.root());
+    }
+}
@@ -6,6 +6,7 @@ pub mod merkle;
 pub mod state;
 
 use instructions::*;
+use state::{LeafData, TreeLeaf, Verdict};
 
 declare_id!("4qn7TjxgnALkV5wjqSjeedSPx8XbacSYNKH4Gv54QEQC");
 
@@ -23,8 +24,16 @@ pub mod bouncer {
         instructions::create_list(ctx, list_id, policy, storage_kind, flags)
     }
 
-    pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
-        instructions::set_authority(ctx, new_authority)
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        instructions::propose_authority(ctx, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority(ctx)
+    }
+
+    pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+        instructions::cancel_authority_transfer(ctx)
     }
 
     pub fn set_policy(ctx: Context<SetPolicy>, new_policy: u8) -> Result<()> {
@@ -35,24 +44,134 @@ pub mod bouncer {
         instructions::freeze_list(ctx)
     }
 
+    pub fn close_list(ctx: Context<CloseList>) -> Result<()> {
+        instructions::close_list(ctx)
+    }
+
+    pub fn add_delegate(
+        ctx: Context<AddDelegate>,
+        operator: Pubkey,
+        permissions: u16,
+    ) -> Result<()> {
+        instructions::add_delegate(ctx, operator, permissions)
+    }
+
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>, operator: Pubkey) -> Result<()> {
+        instructions::revoke_delegate(ctx, operator)
+    }
+
     pub fn upsert_entry(
         ctx: Context<UpsertEntry>,
         subject: Pubkey,
         status: u8,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
     ) -> Result<()> {
-        instructions::upsert_entry(ctx, subject, status)
+        instructions::upsert_entry(ctx, subject, status, valid_from, valid_until)
     }
 
     pub fn remove_entry(ctx: Context<RemoveEntry>, subject: Pubkey) -> Result<()> {
         instructions::remove_entry(ctx, subject)
     }
 
+    pub fn upsert_entries<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpsertEntries<'info>>,
+        subjects: Vec<Pubkey>,
+        statuses: Vec<u8>,
+        valid_froms: Option<Vec<i64>>,
+        valid_untils: Option<Vec<i64>>,
+    ) -> Result<()> {
+        instructions::upsert_entries(ctx, subjects, statuses, valid_froms, valid_untils)
+    }
+
+    pub fn remove_entries<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveEntries<'info>>,
+        subjects: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::remove_entries(ctx, subjects)
+    }
+
     pub fn assert_allowed(
         ctx: Context<AssertAllowed>,
         subject: Pubkey,
         proof: Vec<u8>,
+        leaf: Option<LeafData>,
+    ) -> Result<()> {
+        instructions::assert_allowed(ctx, subject, proof, leaf)
+    }
+
+    pub fn check_status(
+        ctx: Context<CheckStatus>,
+        subject: Pubkey,
+        proof: Vec<u8>,
+        leaf: Option<LeafData>,
+    ) -> Result<Verdict> {
+        instructions::check_status(ctx, subject, proof, leaf)
+    }
+
+    pub fn prove_status(
+        ctx: Context<ProveStatus>,
+        subject: Pubkey,
+        leaf_value: u8,
+        proof: Vec<u8>,
+        leaf: Option<LeafData>,
+    ) -> Result<bool> {
+        instructions::prove_status(ctx, subject, leaf_value, proof, leaf)
+    }
+
+    pub fn create_group(
+        ctx: Context<CreateGroup>,
+        group_id: u64,
+        combinator: u8,
+        lists: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::create_group(ctx, group_id, combinator, lists)
+    }
+
+    pub fn set_group(ctx: Context<SetGroup>, combinator: u8, lists: Vec<Pubkey>) -> Result<()> {
+        instructions::set_group(ctx, combinator, lists)
+    }
+
+    pub fn close_group(ctx: Context<CloseGroup>) -> Result<()> {
+        instructions::close_group(ctx)
+    }
+
+    pub fn assert_allowed_group(
+        ctx: Context<AssertAllowedGroup>,
+        subject: Pubkey,
+        proofs: Vec<Vec<u8>>,
+        leaves: Vec<Option<LeafData>>,
+    ) -> Result<()> {
+        instructions::assert_allowed_group(ctx, subject, proofs, leaves)
+    }
+
+    pub fn init_compressed_tree(ctx: Context<InitCompressedTree>, depth: u8) -> Result<()> {
+        instructions::init_compressed_tree(ctx, depth)
+    }
+
+    pub fn close_tree(ctx: Context<CloseTree>) -> Result<()> {
+        instructions::close_tree(ctx)
+    }
+
+    pub fn append_leaf(
+        ctx: Context<UpdateTreeLeaf>,
+        subject: Pubkey,
+        root: [u8; 32],
+        proof: Vec<u8>,
+        leaf: Option<LeafData>,
     ) -> Result<()> {
-        instructions::assert_allowed(ctx, subject, proof)
+        instructions::append_leaf(ctx, subject, root, proof, leaf)
+    }
+
+    pub fn replace_leaf(
+        ctx: Context<UpdateTreeLeaf>,
+        subject: Pubkey,
+        root: [u8; 32],
+        old_leaf: TreeLeaf,
+        new_leaf: TreeLeaf,
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        instructions::replace_leaf(ctx, subject, root, old_leaf, new_leaf, proof)
     }
 
     pub fn set_merkle_config(
@@ -62,4 +181,44 @@ pub mod bouncer {
     ) -> Result<()> {
         instructions::set_merkle_config(ctx, depth, root)
     }
+
+    pub fn close_root_history(ctx: Context<CloseRootHistory>) -> Result<()> {
+        instructions::close_root_history(ctx)
+    }
+
+    pub fn configure_root_history(
+        ctx: Context<ConfigureRootHistory>,
+        max_age_slots: u64,
+    ) -> Result<()> {
+        instructions::configure_root_history(ctx, max_age_slots)
+    }
+
+    pub fn write_proof_buffer(
+        ctx: Context<WriteProofBuffer>,
+        proof: Vec<u8>,
+        leaf: Option<LeafData>,
+    ) -> Result<()> {
+        instructions::write_proof_buffer(ctx, proof, leaf)
+    }
+
+    pub fn close_proof_buffer(ctx: Context<CloseProofBuffer>) -> Result<()> {
+        instructions::close_proof_buffer(ctx)
+    }
+
+    pub fn prove_membership(
+        ctx: Context<ProveMembership>,
+        subject: Pubkey,
+        proof: Vec<u8>,
+        leaf: Option<LeafData>,
+    ) -> Result<()> {
+        instructions::prove_membership(ctx, subject, proof, leaf)
+    }
+
+    pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+        instructions::close_receipt(ctx)
+    }
+
+    pub fn set_min_tier(ctx: Context<SetMinTier>, min_tier: u16) -> Result<()> {
+        instructions::set_min_tier(ctx, min_tier)
+    }
 }
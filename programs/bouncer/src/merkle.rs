@@ -1,33 +1,30 @@
+//! On-chain side of the sparse Merkle tree rules. Hashing and proof encoding
+//! live in the `bouncer-merkle` crate so off-chain builders use the same code.
+
+use std::borrow::Cow;
+
 use anchor_lang::prelude::*;
+use bouncer_merkle::MerkleError;
+
+pub use bouncer_merkle::{
+    empty_root, empty_subtree_hashes, get_bit_le, hash_node, key_hash, leaf_hash,
+    proof_bitmap_len,
+};
 
 use crate::{
     errors::BouncerError,
-    state::{MERKLE_MAX_DEPTH, POLICY_ALLOWLIST, POLICY_BLOCKLIST},
+    state::{LeafData, POLICY_ALLOWLIST, POLICY_BLOCKLIST},
 };
 
-const ZERO_LEAF: [u8; 32] = [0u8; 32];
-
-fn hash_leaf_one(key_hash: &[u8; 32]) -> [u8; 32] {
-    solana_sha256_hasher::hashv(&[
-        b"bouncer:leaf".as_ref(),
-        key_hash.as_ref(),
-        &[1u8],
-    ])
-    .to_bytes()
-}
-
-fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-    solana_sha256_hasher::hashv(&[
-        b"bouncer:node".as_ref(),
-        left.as_ref(),
-        right.as_ref(),
-    ])
-    .to_bytes()
-}
-
-fn get_bit_le(bytes: &[u8; 32], bit_index: usize) -> bool {
-    let byte = bytes[bit_index / 8];
-    ((byte >> (bit_index % 8)) & 1) == 1
+impl From<MerkleError> for BouncerError {
+    fn from(err: MerkleError) -> Self {
+        match err {
+            MerkleError::InvalidDepth | MerkleError::LeafCollision => {
+                BouncerError::InvalidMerkleConfig
+            }
+            MerkleError::InvalidProofLength => BouncerError::InvalidProofLength,
+        }
+    }
 }
 
 pub fn expected_leaf_value_for_policy(policy: u8) -> Result<u8> {
@@ -38,46 +35,42 @@ pub fn expected_leaf_value_for_policy(policy: u8) -> Result<u8> {
     }
 }
 
-pub fn verify_sparse_merkle_proof(
-    root: [u8; 32],
+/// Normalize `proof` to the uncompressed `depth * 32`-byte form. See
+/// `bouncer_merkle::expand_proof` for the compressed encoding.
+pub fn expand_proof(depth: u8, proof: &[u8]) -> Result<Cow<'_, [u8]>> {
+    bouncer_merkle::expand_proof(depth, proof).map_err(|err| error!(BouncerError::from(err)))
+}
+
+/// Nodes on the path from `leaf` at `key_hash` up to the root:
+/// `path[0]` is the leaf, `path[depth]` is the root. `proof` may be in either
+/// encoding accepted by `expand_proof`.
+pub fn compute_sparse_merkle_path_for_leaf(
     depth: u8,
     key_hash: [u8; 32],
-    expected_leaf_value: u8,
+    leaf: [u8; 32],
     proof: &[u8],
-) -> Result<()> {
-    require!(
-        depth > 0 && depth <= MERKLE_MAX_DEPTH,
-        BouncerError::InvalidMerkleConfig
-    );
-    require!(
-        expected_leaf_value == 0 || expected_leaf_value == 1,
-        BouncerError::InvalidMerkleConfig
-    );
-
-    let depth_usize = depth as usize;
-    require!(
-        proof.len() == depth_usize * 32,
-        BouncerError::InvalidProofLength
-    );
-
-    let mut current = if expected_leaf_value == 0 {
-        ZERO_LEAF
-    } else {
-        hash_leaf_one(&key_hash)
-    };
-
-    for i in 0..depth_usize {
-        let mut sibling = [0u8; 32];
-        sibling.copy_from_slice(&proof[i * 32..(i + 1) * 32]);
+) -> Result<Vec<[u8; 32]>> {
+    bouncer_merkle::compute_path_for_leaf(depth, &key_hash, leaf, proof)
+        .map_err(|err| error!(BouncerError::from(err)))
+}
 
-        let bit = get_bit_le(&key_hash, i);
-        current = if bit {
-            hash_node(&sibling, &current)
-        } else {
-            hash_node(&current, &sibling)
-        };
+/// Leaf node for a subject whose leaf is absent (`leaf_value` 0) or present
+/// (1), committing to `data` when given. Only present leaves carry data.
+pub fn leaf_node(key_hash: &[u8; 32], leaf_value: u8, data: Option<&LeafData>) -> Result<[u8; 32]> {
+    match (leaf_value, data) {
+        (0, None) => Ok(leaf_hash(key_hash, 0)),
+        (1, None) => Ok(leaf_hash(key_hash, 1)),
+        (1, Some(data)) => Ok(data.hash(key_hash)),
+        _ => err!(BouncerError::InvalidStatus),
     }
+}
 
-    require!(current == root, BouncerError::InvalidMerkleProof);
-    Ok(())
+pub fn compute_sparse_merkle_root_for_leaf(
+    depth: u8,
+    key_hash: [u8; 32],
+    leaf: [u8; 32],
+    proof: &[u8],
+) -> Result<[u8; 32]> {
+    bouncer_merkle::compute_root_for_leaf(depth, &key_hash, leaf, proof)
+        .map_err(|err| error!(BouncerError::from(err)))
 }
@@ -2,20 +2,59 @@ use anchor_lang::prelude::*;
 
 pub const LIST_VERSION: u8 = 1;
 pub const ENTRY_VERSION: u8 = 1;
+pub const DELEGATE_VERSION: u8 = 1;
+pub const GROUP_VERSION: u8 = 1;
+pub const ROOT_HISTORY_VERSION: u8 = 1;
+pub const PROOF_BUFFER_VERSION: u8 = 1;
+pub const RECEIPT_VERSION: u8 = 1;
 
 pub const POLICY_ALLOWLIST: u8 = 0;
 pub const POLICY_BLOCKLIST: u8 = 1;
 
 pub const STORAGE_DIRECT_PDA: u8 = 0;
 pub const STORAGE_MERKLE_ROOT: u8 = 1;
+pub const STORAGE_COMPRESSED_TREE: u8 = 2;
 
-pub const MERKLE_MAX_DEPTH: u8 = 64;
+pub const MERKLE_MAX_DEPTH: u8 = bouncer_merkle::MAX_DEPTH;
+
+// Bounded so the tree account (changelog of full paths) stays under the
+// 10 KiB limit for accounts created through CPI.
+pub const TREE_MAX_DEPTH: u8 = 32;
+pub const TREE_CHANGELOG_LEN: usize = 8;
+
+// Superseded Merkle roots kept by a list's RootHistory
+pub const ROOT_HISTORY_LEN: usize = 8;
+
+// Largest proof a ProofBuffer holds: a compressed proof at max depth
+// (8-byte bitmap + 64 siblings) is 8 bytes longer than an uncompressed one.
+pub const PROOF_BUFFER_MAX_LEN: usize = 8 + MERKLE_MAX_DEPTH as usize * 32;
 
 pub const ENTRY_STATUS_UNSET: u8 = 0;
 pub const ENTRY_STATUS_ALLOW: u8 = 1;
 pub const ENTRY_STATUS_BLOCK: u8 = 2;
 
 pub const FLAG_FROZEN: u16 = 1 << 0;
+pub const FLAG_ROOT_HISTORY: u16 = 1 << 1; // list has a RootHistory that root changes must feed
+
+// Verdict reason codes returned by check_status
+pub const REASON_ALLOWED: u8 = 0;
+pub const REASON_NOT_LISTED: u8 = 1; // allowlist has no active entry for the subject
+pub const REASON_BLOCKED: u8 = 2;
+pub const REASON_OUTSIDE_VALIDITY: u8 = 3; // entry exists but is expired or not yet valid
+pub const REASON_TIER_TOO_LOW: u8 = 4;
+pub const REASON_ENTRY_MISMATCH: u8 = 5; // entry account is not the subject's entry PDA
+pub const REASON_INVALID_PROOF: u8 = 6;
+pub const REASON_INVALID_LIST: u8 = 7; // list or auxiliary accounts are misconfigured
+
+pub const COMBINATOR_AND: u8 = 0;
+pub const COMBINATOR_OR: u8 = 1;
+
+pub const MAX_GROUP_LISTS: usize = 8;
+
+pub const PERM_ENTRY_OPERATOR: u16 = 1 << 0; // upsert/remove entries
+pub const PERM_MERKLE_PUBLISHER: u16 = 1 << 1; // set merkle config / rotate roots
+pub const PERM_ADMIN: u16 = 1 << 2; // set policy, freeze
+pub const PERM_ALL: u16 = PERM_ENTRY_OPERATOR | PERM_MERKLE_PUBLISHER | PERM_ADMIN;
 
 #[account]
 pub struct List {
@@ -27,13 +66,18 @@ pub struct List {
     pub list_id: u64,
 
     pub policy: u8,       // 0=Allowlist, 1=Blocklist
-    pub storage_kind: u8, // 0=DirectPda, 1=SparseMerkleRoot, 2=ReservedCompressedTree
+    pub storage_kind: u8, // 0=DirectPda, 1=SparseMerkleRoot, 2=CompressedTree
     pub flags: u16,
 
     pub entry_count: u32,
-    pub reserved0: u32,
+    pub delegate_count: u16,
+    pub reserved0: u16,
 
     pub storage_config: [u8; 96],
+
+    pub pending_authority: Pubkey, // Pubkey::default() when no transfer is in progress
+
+    pub revision: u64, // bumped by every change that can alter a verdict
 }
 
 impl List {
@@ -43,6 +87,29 @@ impl List {
         (self.flags & FLAG_FROZEN) != 0
     }
 
+    pub fn has_root_history(&self) -> bool {
+        (self.flags & FLAG_ROOT_HISTORY) != 0
+    }
+
+    /// Record a change to entries, roots or policy.
+    pub fn bump_revision(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    /// True if `signer` is the list authority, or holds a delegate for this
+    /// list granting every bit in `permission`. The delegate PDA itself is
+    /// validated by the instruction's seeds constraint.
+    pub fn is_authorized(
+        &self,
+        signer: &Pubkey,
+        delegate: Option<&Delegate>,
+        permission: u16,
+    ) -> bool {
+        self.authority == *signer
+            || delegate
+                .is_some_and(|d| d.operator == *signer && (d.permissions & permission) == permission)
+    }
+
     pub fn merkle_depth(&self) -> u8 {
         self.storage_config[0]
     }
@@ -57,6 +124,62 @@ impl List {
         self.storage_config[0] = depth;
         self.storage_config[1..33].copy_from_slice(&root);
     }
+
+    /// Lowest `LeafData::tier` a Merkle allowlist accepts. Plain present
+    /// leaves count as tier 0.
+    pub fn merkle_min_tier(&self) -> u16 {
+        u16::from_le_bytes([self.storage_config[33], self.storage_config[34]])
+    }
+
+    pub fn set_merkle_min_tier(&mut self, min_tier: u16) {
+        self.storage_config[33..35].copy_from_slice(&min_tier.to_le_bytes());
+    }
+}
+
+/// Structured result of evaluating a subject against a list.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Verdict {
+    pub allowed: bool,
+    pub reason: u8,       // REASON_*
+    pub entry_status: u8, // status recorded by the matched entry or leaf, 0 if none
+    pub list_revision: u64, // List::revision the subject was judged against
+}
+
+/// Metadata committed to by a Merkle leaf, supplied by the caller alongside
+/// the proof. Hashes like `bouncer_merkle::LeafData`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeafData {
+    pub status: u8,       // ENTRY_STATUS_ALLOW / ENTRY_STATUS_BLOCK
+    pub valid_until: i64, // unix timestamp (exclusive), 0=no expiry
+    pub tier: u16,
+}
+
+/// One side of a compressed-tree leaf update: `value` 0 = absent, 1 = present,
+/// with `data` the metadata a present leaf commits to, if any.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeLeaf {
+    pub value: u8,
+    pub data: Option<LeafData>,
+}
+
+impl LeafData {
+    pub fn hash(&self, key_hash: &[u8; 32]) -> [u8; 32] {
+        bouncer_merkle::LeafData {
+            status: self.status,
+            valid_until: self.valid_until,
+            tier: self.tier,
+        }
+        .hash(key_hash)
+    }
+
+    /// Status in effect at `now`; expired leaves behave as unset.
+    pub fn status_at(&self, now: i64) -> u8 {
+        if self.valid_until != 0 && now >= self.valid_until {
+            ENTRY_STATUS_UNSET
+        } else {
+            self.status
+        }
+    }
 }
 
 #[account]
@@ -68,10 +191,204 @@ pub struct Entry {
 
     pub list: Pubkey,
     pub subject: Pubkey,
+
+    pub valid_from: i64,  // unix timestamp, 0=no lower bound
+    pub valid_until: i64, // unix timestamp (exclusive), 0=no expiry
 }
 
 impl Entry {
     pub const LEN: usize = 96;
+
+    /// Status in effect at `now`. Outside `[valid_from, valid_until)` the entry
+    /// behaves as if it did not exist.
+    pub fn status_at(&self, now: i64) -> u8 {
+        let started = self.valid_from == 0 || now >= self.valid_from;
+        let expired = self.valid_until != 0 && now >= self.valid_until;
+        if started && !expired {
+            self.status
+        } else {
+            ENTRY_STATUS_UNSET
+        }
+    }
+}
+
+#[account]
+pub struct Delegate {
+    pub version: u8, // = 1
+    pub bump: u8,
+    pub permissions: u16, // PERM_* bitmask
+
+    pub list: Pubkey,
+    pub operator: Pubkey,
+}
+
+impl Delegate {
+    pub const LEN: usize = 96;
+}
+
+#[account]
+pub struct ListGroup {
+    pub version: u8, // = 1
+    pub bump: u8,
+    pub combinator: u8, // 0=And, 1=Or
+    pub list_count: u8,
+
+    pub authority: Pubkey,
+    pub creator: Pubkey,
+    pub group_id: u64,
+
+    pub lists: [Pubkey; MAX_GROUP_LISTS],
+}
+
+impl ListGroup {
+    pub const LEN: usize = 384;
+
+    pub fn members(&self) -> &[Pubkey] {
+        &self.lists[..self.list_count as usize]
+    }
+}
+
+/// Merkle proof a subject stores for a list at PDA ["proof", list, subject],
+/// for callers such as the transfer hook that cannot pass a proof as
+/// instruction data. Verified only when used, so it may go stale when the
+/// root moves on and the subject has to rewrite it.
+#[account]
+pub struct ProofBuffer {
+    pub version: u8, // = 1
+    pub bump: u8,
+
+    pub list: Pubkey,
+    pub subject: Pubkey,
+
+    pub leaf: Option<LeafData>, // metadata leaf, None for a plain present/absent leaf
+    pub proof: Vec<u8>,         // either proof encoding, at most PROOF_BUFFER_MAX_LEN bytes
+}
+
+impl ProofBuffer {
+    pub const LEN: usize = 8 + 2 + 32 + 32 + (1 + 11) + (4 + PROOF_BUFFER_MAX_LEN);
+}
+
+/// Record that a Merkle proof for `subject` was verified against `root`, at
+/// PDA ["receipt", list, subject]. `assert_allowed` accepts it in place of a
+/// proof for as long as the list still accepts `root`.
+#[account]
+pub struct Receipt {
+    pub version: u8, // = 1
+    pub bump: u8,
+
+    pub list: Pubkey,
+    pub subject: Pubkey,
+    pub payer: Pubkey, // funded the receipt; gets the rent back on close
+
+    pub root: [u8; 32],
+    pub leaf_hash: [u8; 32],    // leaf the proof showed
+    pub leaf: Option<LeafData>, // metadata behind `leaf_hash`, None for a plain leaf
+    pub verified_slot: u64,
+}
+
+impl Receipt {
+    pub const LEN: usize = 192;
+}
+
+/// Recently superseded roots of a `STORAGE_MERKLE_ROOT` list, at PDA
+/// ["roots", list]. Proofs built against one of these keep verifying until the
+/// root ages out, so publishing a new root does not break in-flight transactions.
+#[account]
+pub struct RootHistory {
+    pub version: u8, // = 1
+    pub bump: u8,
+    pub depth: u8,      // depth the stored roots were published at
+    pub next_index: u8, // ring slot written by the next push
+    pub count: u8,      // populated slots
+    pub reserved: [u8; 3],
+
+    pub list: Pubkey,
+    pub max_age_slots: u64, // required > 0; an older history with 0 accepts no old roots
+
+    pub roots: [[u8; 32]; ROOT_HISTORY_LEN],
+    pub retired_slots: [u64; ROOT_HISTORY_LEN], // slot each root was superseded
+}
+
+impl RootHistory {
+    pub const LEN: usize = 384;
+
+    /// Record `root` as superseded at `slot`. Roots of another depth can no
+    /// longer verify anything, so a depth change clears the ring.
+    pub fn push(&mut self, root: [u8; 32], depth: u8, slot: u64) {
+        if depth != self.depth {
+            self.depth = depth;
+            self.count = 0;
+            self.next_index = 0;
+        }
+        let index = self.next_index as usize;
+        self.roots[index] = root;
+        self.retired_slots[index] = slot;
+        self.next_index = ((index + 1) % ROOT_HISTORY_LEN) as u8;
+        self.count = (self.count as usize + 1).min(ROOT_HISTORY_LEN) as u8;
+    }
+
+    pub fn contains(&self, root: &[u8; 32], depth: u8, now_slot: u64) -> bool {
+        depth == self.depth
+            && self.max_age_slots > 0
+            && (0..self.count as usize).any(|i| {
+                self.roots[i] == *root
+                    && now_slot.saturating_sub(self.retired_slots[i]) <= self.max_age_slots
+            })
+    }
+}
+
+/// On-chain concurrent sparse Merkle tree for `STORAGE_COMPRESSED_TREE` lists,
+/// at PDA ["tree", list]. Leaves use the same hashing as `STORAGE_MERKLE_ROOT`.
+/// The changelog keeps the last `TREE_CHANGELOG_LEN` roots with the path
+/// written by each update, so proofs built against a recent root can be
+/// fast-forwarded to the current one.
+#[account(zero_copy)]
+#[repr(C)]
+pub struct CompressedTree {
+    pub list: Pubkey,
+    pub depth: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+    pub sequence: u64,     // number of updates applied since init
+    pub active_index: u64, // changelog slot holding the current root
+    pub changelog: [ChangeLogEntry; TREE_CHANGELOG_LEN],
+}
+
+#[zero_copy]
+#[repr(C)]
+pub struct ChangeLogEntry {
+    pub root: [u8; 32],
+    pub key_hash: [u8; 32],
+    pub path: [[u8; 32]; TREE_MAX_DEPTH as usize], // path[i] = node at level i (0 = leaf)
+}
+
+impl CompressedTree {
+    pub const LEN: usize = 8 + std::mem::size_of::<CompressedTree>();
+
+    pub fn current_root(&self) -> [u8; 32] {
+        self.changelog[self.active_index as usize].root
+    }
+
+    /// Changelog slots from oldest to newest that are still populated.
+    pub fn live_slots(&self) -> impl Iterator<Item = usize> {
+        let live = (self.sequence as usize + 1).min(TREE_CHANGELOG_LEN);
+        let newest = self.active_index as usize;
+        (0..live).rev().map(move |age| {
+            (newest + TREE_CHANGELOG_LEN - age) % TREE_CHANGELOG_LEN
+        })
+    }
+
+    /// Whether `root` is still in the changelog and no later change touched
+    /// `key_hash`, so that leaf reads the same now as it did under `root`.
+    pub fn leaf_unchanged_since(&self, root: &[u8; 32], key_hash: &[u8; 32]) -> bool {
+        let slots: Vec<usize> = self.live_slots().collect();
+        match slots.iter().rposition(|&slot| self.changelog[slot].root == *root) {
+            Some(start) => slots[start + 1..]
+                .iter()
+                .all(|&slot| self.changelog[slot].key_hash != *key_hash),
+            None => false,
+        }
+    }
 }
 
 #[event]
@@ -91,6 +408,20 @@ pub struct AuthorityChanged {
     pub new_authority: Pubkey,
 }
 
+#[event]
+pub struct AuthorityTransferProposed {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
 #[event]
 pub struct PolicyChanged {
     pub list: Pubkey,
@@ -103,11 +434,20 @@ pub struct ListFrozen {
     pub list: Pubkey,
 }
 
+#[event]
+pub struct ListClosed {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub refund_to: Pubkey,
+}
+
 #[event]
 pub struct EntryUpserted {
     pub list: Pubkey,
     pub subject: Pubkey,
     pub status: u8,
+    pub valid_from: i64,
+    pub valid_until: i64,
 }
 
 #[event]
@@ -116,6 +456,51 @@ pub struct EntryRemoved {
     pub subject: Pubkey,
 }
 
+#[event]
+pub struct DelegateAdded {
+    pub list: Pubkey,
+    pub operator: Pubkey,
+    pub permissions: u16,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub list: Pubkey,
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct GroupSet {
+    pub group: Pubkey,
+    pub authority: Pubkey,
+    pub combinator: u8,
+    pub lists: Vec<Pubkey>,
+}
+
+#[event]
+pub struct GroupClosed {
+    pub group: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TreeLeafUpdated {
+    pub list: Pubkey,
+    pub subject: Pubkey,
+    pub old_value: u8,
+    pub new_value: u8,
+    pub new_leaf: Option<LeafData>,
+    pub root: [u8; 32],
+    pub sequence: u64,
+}
+
+#[event]
+pub struct TreeClosed {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub refund_to: Pubkey,
+}
+
 #[event]
 pub struct MerkleConfigSet {
     pub list: Pubkey,
@@ -123,3 +508,24 @@ pub struct MerkleConfigSet {
     pub depth: u8,
     pub root: [u8; 32],
 }
+
+#[event]
+pub struct MinTierSet {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub min_tier: u16,
+}
+
+#[event]
+pub struct RootHistoryConfigured {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub max_age_slots: u64,
+}
+
+#[event]
+pub struct RootHistoryClosed {
+    pub list: Pubkey,
+    pub authority: Pubkey,
+    pub refund_to: Pubkey,
+}
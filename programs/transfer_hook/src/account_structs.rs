@@ -1,7 +1,8 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::Config;
+use crate::state::{BalanceOverride, Config, MintConfig, TransferTracker};
+use crate::ErrorCode;
 
 #[derive(Accounts)]
 pub struct InitializeExtraAccountMetaList<'info> {
@@ -17,10 +18,44 @@ pub struct InitializeExtraAccountMetaList<'info> {
     pub extra_account_meta_list: AccountInfo<'info>,
 
     pub mint: InterfaceAccount<'info, Mint>,
-    pub config: Account<'info, Config>,
+    /// CHECK: MintConfig ["config", mint] or legacy global Config ["config"], validated in resolve_config
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: MintConfig PDA ["config", mint]; may be uninitialized, in which case the
+    /// global Config may be used
+    #[account(seeds = [b"config", mint.key().as_ref()], bump)]
+    pub mint_config: UncheckedAccount<'info>,
     pub bouncer_program: Program<'info, bouncer::program::Bouncer>,
-    pub bouncer_list: Account<'info, bouncer::state::List>,
-    pub entry_account: Account<'info, bouncer::state::Entry>,
+    /// CHECK: bouncer List or ListGroup; owner checked here, contents validated by bouncer
+    #[account(owner = bouncer_program.key())]
+    pub bouncer_list: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: PDA ["extra-account-metas", mint]
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: AccountInfo<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: MintConfig ["config", mint] or legacy global Config ["config"], validated in resolve_config
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: MintConfig PDA ["config", mint]; may be uninitialized, in which case the
+    /// global Config may be used
+    #[account(seeds = [b"config", mint.key().as_ref()], bump)]
+    pub mint_config: UncheckedAccount<'info>,
+    pub bouncer_program: Program<'info, bouncer::program::Bouncer>,
+    /// CHECK: bouncer List or ListGroup; owner checked here, contents validated by bouncer
+    #[account(owner = bouncer_program.key())]
+    pub bouncer_list: UncheckedAccount<'info>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
@@ -45,13 +80,26 @@ pub struct TransferHook<'info> {
     )]
     pub extra_account_meta_list: UncheckedAccount<'info>,
 
-    /// CHECK: protocol config
-    #[account(seeds = [b"config"], bump = config.bump)]
-    pub config: Account<'info, Config>,
-    pub bouncer_list: Account<'info, bouncer::state::List>,
+    /// CHECK: MintConfig ["config", mint] or legacy global Config ["config"], validated in resolve_config
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: bouncer List or ListGroup; owner checked here, contents validated by bouncer
+    #[account(owner = bouncer_program.key())]
+    pub bouncer_list: UncheckedAccount<'info>,
     pub bouncer_program: Program<'info, bouncer::program::Bouncer>,
-    pub entry_account: Account<'info, bouncer::state::Entry>,
+    /// CHECK: bouncer Entry PDA (or ProofBuffer PDA for Merkle lists) for the destination owner;
+    /// may be uninitialized, validated by bouncer
+    pub entry_account: UncheckedAccount<'info>,
     pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: bouncer Entry PDA (or ProofBuffer PDA for Merkle lists) for the source owner;
+    /// may be uninitialized, validated by bouncer
+    pub source_entry_account: UncheckedAccount<'info>,
+    /// CHECK: TransferTracker PDA ["tracker", mint, source owner]; may be uninitialized
+    /// while the mint has no velocity limit, validated in record_velocity
+    #[account(mut)]
+    pub transfer_tracker: UncheckedAccount<'info>,
+    /// CHECK: BalanceOverride PDA ["balance", mint, destination owner]; may be uninitialized,
+    /// validated in check_balance
+    pub balance_override: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -64,32 +112,285 @@ pub struct InitializeConfig<'info> {
         payer = payer,
         space = 8+Config::INIT_SPACE,
         seeds = [b"config"],
-        bump
+        bump,
+        constraint = config.admin == Pubkey::default() || config.admin == payer.key() @ ErrorCode::Unauthorized
     )]
     pub config: Account<'info, Config>,
 
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program: Program<'info, crate::program::MarketTransferHook>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
 
-    #[account(seeds = [b"config"], bump = config.bump)]
+#[derive(Accounts)]
+pub struct TransferConfigAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptConfigAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = pending_admin @ ErrorCode::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
     pub config: Account<'info, Config>,
 }
 
 #[derive(Accounts)]
 pub struct CloseConfig<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
 
-    /// CHECK: Config account to close (may have old structure, so we use UncheckedAccount)
     #[account(
         mut,
+        close = admin,
+        has_one = admin @ ErrorCode::Unauthorized,
         seeds = [b"config"],
-        bump
+        bump = config.bump
     )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: global Config ["config"] in the legacy layout, checked in the handler
+    #[account(mut, owner = crate::ID, seeds = [b"config"], bump)]
     pub config: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program: Program<'info, crate::program::MarketTransferHook>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMintConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint_authority: Signer<'info>,
+
+    #[account(
+        constraint = mint.mint_authority == COption::Some(mint_authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MintConfig::INIT_SPACE,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMintConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetAmountPolicy<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetVelocityPolicy<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxBalance<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct SetBalanceOverride<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + BalanceOverride::INIT_SPACE,
+        seeds = [b"balance", mint_config.mint.as_ref(), owner.as_ref()],
+        bump
+    )]
+    pub balance_override: Account<'info, BalanceOverride>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveBalanceOverride<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        seeds = [b"balance", mint_config.mint.as_ref(), balance_override.owner.as_ref()],
+        bump = balance_override.bump
+    )]
+    pub balance_override: Account<'info, BalanceOverride>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct InitializeTransferTracker<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TransferTracker::INIT_SPACE,
+        seeds = [b"tracker", mint.key().as_ref(), owner.as_ref()],
+        bump
+    )]
+    pub transfer_tracker: Account<'info, TransferTracker>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferMintConfigAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptMintConfigAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = pending_admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMintConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        close = refund_to,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [b"config", mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// CHECK: rent refund destination
+    #[account(mut)]
+    pub refund_to: UncheckedAccount<'info>,
 }
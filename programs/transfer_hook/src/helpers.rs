@@ -1,46 +1,640 @@
 use anchor_lang::{prelude::*, solana_program};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        transfer_hook::{TransferHook as TransferHookExtension, TransferHookAccount},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_type_length_value::state::TlvStateBorrowed;
+
+use bouncer::state::{
+    LeafData, List, ListGroup, ProofBuffer, Verdict, COMBINATOR_AND, COMBINATOR_OR,
+    STORAGE_COMPRESSED_TREE, STORAGE_DIRECT_PDA, STORAGE_MERKLE_ROOT, REASON_BLOCKED,
+    REASON_ENTRY_MISMATCH, REASON_INVALID_LIST, REASON_INVALID_PROOF, REASON_NOT_LISTED,
+    REASON_OUTSIDE_VALIDITY, REASON_TIER_TOO_LOW,
+};
 
 use crate::account_structs::TransferHook;
+use crate::state::{
+    BalanceOverride, Config, LegacyConfig, MintConfig, TransferTracker, CHECK_BOTH, CHECK_RECIPIENT,
+    CHECK_SENDER,
+};
 use crate::ErrorCode;
 
-pub fn check_whitelist(
+// Account indices in the Execute instruction
+// index 0-3 are the accounts required for token transfer (source, mint, destination, owner)
+// index 4 is address of ExtraAccountMetaList account
+pub const SOURCE_TOKEN_INDEX: u8 = 0;
+pub const MINT_INDEX: u8 = 1;
+pub const DESTINATION_TOKEN_INDEX: u8 = 2;
+pub const BOUNCER_LIST_INDEX: u8 = 6;
+pub const BOUNCER_PROGRAM_INDEX: u8 = 7;
+// index 11 = the source owner's TransferTracker
+// index 12 = the destination owner's BalanceOverride
+// index 13.. = one (list, destination entry, source entry) triple per group member,
+// or for a Merkle list its RootHistory / CompressedTree account
+pub const GROUP_MEMBERS_INDEX: u8 = 13;
+const GROUP_MEMBER_STRIDE: usize = 3;
+
+// Offset of `owner` in a token account (after the 32-byte mint)
+const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+
+// Bouncer PDA [`prefix`, list at `list_index`, owner of the token account at
+// `token_index`]: the party's Entry ("entry") or ProofBuffer ("proof")
+fn party_meta(prefix: &[u8], list_index: u8, token_index: u8) -> Result<ExtraAccountMeta> {
+    ExtraAccountMeta::new_external_pda_with_seeds(
+        BOUNCER_PROGRAM_INDEX,
+        &[
+            Seed::Literal {
+                bytes: prefix.to_vec(),
+            },
+            Seed::AccountKey { index: list_index },
+            Seed::AccountData {
+                account_index: token_index,
+                data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                length: 32,
+            },
+        ],
+        false,
+        false,
+    )
+    .map_err(to_anchor_error_tlv)
+}
+
+fn pubkey_meta(key: &Pubkey) -> Result<ExtraAccountMeta> {
+    ExtraAccountMeta::new_with_pubkey(
+        &spl_tlv_account_resolution::solana_pubkey::Pubkey::new_from_array(key.to_bytes()),
+        false,
+        false,
+    )
+    .map_err(to_anchor_error_tlv)
+}
+
+// This program's PDA [`prefix`, mint, owner of the token account at
+// `token_index`]: a TransferTracker ("tracker") or BalanceOverride ("balance")
+fn holder_meta(prefix: &[u8], token_index: u8, is_writable: bool) -> Result<ExtraAccountMeta> {
+    ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: prefix.to_vec(),
+            },
+            Seed::AccountKey { index: MINT_INDEX },
+            Seed::AccountData {
+                account_index: token_index,
+                data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                length: 32,
+            },
+        ],
+        false,
+        is_writable,
+    )
+    .map_err(to_anchor_error_tlv)
+}
+
+// Bouncer PDA [`seed`, configured list], e.g. its RootHistory or CompressedTree
+fn list_aux_meta(seed: &[u8]) -> Result<ExtraAccountMeta> {
+    ExtraAccountMeta::new_external_pda_with_seeds(
+        BOUNCER_PROGRAM_INDEX,
+        &[
+            Seed::Literal {
+                bytes: seed.to_vec(),
+            },
+            Seed::AccountKey {
+                index: BOUNCER_LIST_INDEX,
+            },
+        ],
+        false,
+        false,
+    )
+    .map_err(to_anchor_error_tlv)
+}
+
+// What the configured bouncer account is; decides which extra accounts the hook needs
+pub enum ListTarget {
+    // Direct-PDA List: each party's Entry PDA
+    Direct,
+    // Merkle-root or compressed-tree List: each party's ProofBuffer PDA, plus
+    // the list account at PDA [`aux_seed`, list] that proofs verify against
+    Merkle { aux_seed: &'static [u8] },
+    // ListGroup: entry PDAs for every member list
+    Group { combinator: u8, members: Vec<Pubkey> },
+}
+
+// Extra accounts the hook needs on every Execute, in index order
+pub fn build_extra_account_metas(
+    config: &Pubkey,
+    bouncer_list: &Pubkey,
+    bouncer_program: &Pubkey,
+    token_program: &Pubkey,
+    target: &ListTarget,
+) -> Result<Vec<ExtraAccountMeta>> {
+    let party_prefix: &[u8] = match target {
+        ListTarget::Merkle { .. } => b"proof",
+        _ => b"entry",
+    };
+
+    let mut metas = vec![
+        // index 5 = config account
+        pubkey_meta(config)?,
+        // index 6 = bouncer_list
+        pubkey_meta(bouncer_list)?,
+        // index 7 = bouncer_program
+        pubkey_meta(bouncer_program)?,
+        // index 8 = entry_account (or proof buffer) for the destination owner
+        party_meta(party_prefix, BOUNCER_LIST_INDEX, DESTINATION_TOKEN_INDEX)?,
+        // index 9 = token_program
+        pubkey_meta(token_program)?,
+        // index 10 = source_entry_account (or proof buffer) for the source owner
+        party_meta(party_prefix, BOUNCER_LIST_INDEX, SOURCE_TOKEN_INDEX)?,
+        // index 11 = transfer_tracker for the source owner (writable)
+        holder_meta(b"tracker", SOURCE_TOKEN_INDEX, true)?,
+        // index 12 = balance_override for the destination owner
+        holder_meta(b"balance", DESTINATION_TOKEN_INDEX, false)?,
+    ];
+
+    match target {
+        ListTarget::Direct => {}
+        ListTarget::Merkle { aux_seed } => metas.push(list_aux_meta(aux_seed)?),
+        ListTarget::Group { members, .. } => {
+            for (i, member) in members.iter().enumerate() {
+                let list_index = GROUP_MEMBERS_INDEX + (i * GROUP_MEMBER_STRIDE) as u8;
+                metas.push(pubkey_meta(member)?);
+                metas.push(party_meta(b"entry", list_index, DESTINATION_TOKEN_INDEX)?);
+                metas.push(party_meta(b"entry", list_index, SOURCE_TOKEN_INDEX)?);
+            }
+        }
+    }
+
+    Ok(metas)
+}
+
+pub fn list_target(bouncer_list: &AccountInfo) -> Result<ListTarget> {
+    let data = bouncer_list.try_borrow_data()?;
+    let mut data_slice: &[u8] = &data;
+
+    if data.starts_with(ListGroup::DISCRIMINATOR) {
+        let group = ListGroup::try_deserialize(&mut data_slice)?;
+        return Ok(ListTarget::Group {
+            combinator: group.combinator,
+            members: group.members().to_vec(),
+        });
+    }
+
+    let list =
+        List::try_deserialize(&mut data_slice).map_err(|_| error!(ErrorCode::InvalidConfig))?;
+    match list.storage_kind {
+        STORAGE_DIRECT_PDA => Ok(ListTarget::Direct),
+        STORAGE_MERKLE_ROOT => Ok(ListTarget::Merkle { aux_seed: b"roots" }),
+        STORAGE_COMPRESSED_TREE => Ok(ListTarget::Merkle { aux_seed: b"tree" }),
+        _ => err!(ErrorCode::InvalidConfig),
+    }
+}
+
+// `list_target` for building a meta list. The hook only resolves entry PDAs
+// for group members, so every member must be a direct-PDA List; their List
+// accounts are passed in `member_lists`, in group order.
+pub fn meta_list_target(
+    bouncer_list: &AccountInfo,
+    member_lists: &[AccountInfo],
+) -> Result<ListTarget> {
+    let target = list_target(bouncer_list)?;
+
+    if let ListTarget::Group { members, .. } = &target {
+        require!(
+            member_lists.len() >= members.len(),
+            ErrorCode::UnsupportedGroupMember
+        );
+        for (member, member_info) in members.iter().zip(member_lists) {
+            require_keys_eq!(member_info.key(), *member, ErrorCode::UnsupportedGroupMember);
+            require_keys_eq!(
+                *member_info.owner,
+                *bouncer_list.owner,
+                ErrorCode::UnsupportedGroupMember
+            );
+            let data = member_info.try_borrow_data()?;
+            let mut data_slice: &[u8] = &data;
+            let list = List::try_deserialize(&mut data_slice)
+                .map_err(|_| error!(ErrorCode::UnsupportedGroupMember))?;
+            require!(
+                list.storage_kind == STORAGE_DIRECT_PDA,
+                ErrorCode::UnsupportedGroupMember
+            );
+        }
+    }
+
+    Ok(target)
+}
+
+// Resolve the hook config for `mint`: the per-mint `MintConfig` at
+// ["config", mint] if that is what was passed, otherwise the legacy global
+// `Config` at ["config"], which is kept as a fallback during migration.
+pub fn resolve_config(config_info: &AccountInfo, mint: &Pubkey) -> Result<MintConfig> {
+    require_keys_eq!(*config_info.owner, crate::ID, ErrorCode::InvalidConfig);
+
+    let data = config_info.try_borrow_data()?;
+    let mut data_slice: &[u8] = &data;
+
+    if data.starts_with(MintConfig::DISCRIMINATOR) {
+        let mint_config = MintConfig::try_deserialize(&mut data_slice)
+            .map_err(|_| error!(ErrorCode::InvalidConfig))?;
+        let expected = Pubkey::create_program_address(
+            &[b"config", mint.as_ref(), &[mint_config.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| error!(ErrorCode::InvalidConfig))?;
+        require_keys_eq!(config_info.key(), expected, ErrorCode::InvalidConfig);
+        require_keys_eq!(mint_config.mint, *mint, ErrorCode::InvalidConfig);
+        return Ok(mint_config);
+    }
+
+    let config = read_config(&data)?;
+    let expected = Pubkey::create_program_address(&[b"config", &[config.bump]], &crate::ID)
+        .map_err(|_| error!(ErrorCode::InvalidConfig))?;
+    require_keys_eq!(config_info.key(), expected, ErrorCode::InvalidConfig);
+
+    Ok(MintConfig {
+        mint: *mint,
+        bouncer_program_id: config.bouncer_program_id,
+        bouncer_list: config.bouncer_list,
+        bump: config.bump,
+        admin: config.admin,
+        pending_admin: config.pending_admin,
+        check_mode: CHECK_RECIPIENT,
+        min_amount: 0,
+        max_amount: 0,
+        lot_size: 0,
+        velocity_window: 0,
+        velocity_limit: 0,
+        velocity_tier_limits: Vec::new(),
+        max_balance: 0,
+    })
+}
+
+// A mint with a MintConfig at ["config", mint] must use it; only mints without
+// one may fall back to the global Config
+pub fn require_own_config(config: &AccountInfo, mint_config: &AccountInfo) -> Result<()> {
+    if *mint_config.owner == crate::ID && !mint_config.data_is_empty() {
+        require_keys_eq!(config.key(), mint_config.key(), ErrorCode::InvalidConfig);
+    }
+    Ok(())
+}
+
+// Config account an initialized ExtraAccountMetaList points at (index 5)
+pub fn stored_config_key(meta_list: &AccountInfo) -> Result<Pubkey> {
+    let data = meta_list.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&data).map_err(to_anchor_error_tlv)?;
+    let metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&state)
+        .map_err(to_anchor_error_tlv)?;
+    let config_meta = metas.first().ok_or(ErrorCode::InvalidConfig)?;
+    // Discriminator 0 = fixed pubkey
+    require!(config_meta.discriminator == 0, ErrorCode::InvalidConfig);
+    Ok(Pubkey::new_from_array(config_meta.address_config))
+}
+
+// Global Config in either layout; a legacy one has no admin yet
+pub fn read_config(data: &[u8]) -> Result<Config> {
+    if data.len() == Config::LEGACY_LEN && data.starts_with(Config::DISCRIMINATOR) {
+        let legacy = LegacyConfig::deserialize(&mut &data[8..])
+            .map_err(|_| error!(ErrorCode::InvalidConfig))?;
+        return Ok(Config {
+            bouncer_program_id: legacy.bouncer_program_id,
+            bouncer_list: legacy.bouncer_list,
+            bump: legacy.bump,
+            admin: Pubkey::default(),
+            pending_admin: Pubkey::default(),
+        });
+    }
+
+    let mut data_slice = data;
+    Config::try_deserialize(&mut data_slice).map_err(|_| error!(ErrorCode::InvalidConfig))
+}
+
+// Only Token-2022 may invoke the hook: it sets the `transferring` flag on both
+// token accounts for the duration of the CPI, and the mint must name this
+// program as its transfer hook.
+pub fn assert_is_transferring(ctx: &Context<TransferHook>) -> Result<()> {
+    for token_info in [
+        ctx.accounts.source_token.to_account_info(),
+        ctx.accounts.destination_token.to_account_info(),
+    ] {
+        let data = token_info.try_borrow_data()?;
+        let account = StateWithExtensions::<TokenAccountState>::unpack(&data)
+            .map_err(|_| error!(ErrorCode::NotTransferring))?;
+        let extension = account
+            .get_extension::<TransferHookAccount>()
+            .map_err(|_| error!(ErrorCode::NotTransferring))?;
+        require!(bool::from(extension.transferring), ErrorCode::NotTransferring);
+    }
+
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)
+        .map_err(|_| error!(ErrorCode::InvalidTransferHookProgram))?;
+    let extension = mint
+        .get_extension::<TransferHookExtension>()
+        .map_err(|_| error!(ErrorCode::InvalidTransferHookProgram))?;
+    require!(
+        Option::<Pubkey>::from(extension.program_id) == Some(crate::ID),
+        ErrorCode::InvalidTransferHookProgram
+    );
+
+    Ok(())
+}
+
+// Per-transfer amount limits from the mint config; a zero limit is not enforced
+pub fn check_amount(config: &MintConfig, amount: u64) -> Result<()> {
+    require!(
+        config.min_amount == 0 || amount >= config.min_amount,
+        ErrorCode::AmountBelowMinimum
+    );
+    require!(
+        config.max_amount == 0 || amount <= config.max_amount,
+        ErrorCode::AmountAboveMaximum
+    );
+    require!(
+        config.lot_size == 0 || amount % config.lot_size == 0,
+        ErrorCode::AmountNotLotMultiple
+    );
+    Ok(())
+}
+
+pub fn checks_recipient(config: &MintConfig) -> bool {
+    config.check_mode == CHECK_RECIPIENT || config.check_mode == CHECK_BOTH
+}
+
+pub fn checks_sender(config: &MintConfig) -> bool {
+    config.check_mode == CHECK_SENDER || config.check_mode == CHECK_BOTH
+}
+
+// The destination's balance after the transfer may not exceed the mint's
+// max_balance, or the holder's BalanceOverride when one exists
+pub fn check_balance(
     ctx: &Context<TransferHook>,
-    key: Pubkey,
+    config: &MintConfig,
+    owner: Pubkey,
 ) -> Result<()> {
+    if config.max_balance == 0 {
+        return Ok(());
+    }
+
+    let override_info = ctx.accounts.balance_override.to_account_info();
+    let cap = if *override_info.owner == crate::ID && !override_info.data_is_empty() {
+        let data = override_info.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        let balance_override = BalanceOverride::try_deserialize(&mut data_slice)
+            .map_err(|_| error!(ErrorCode::BalanceOverrideMismatch))?;
+        require_keys_eq!(
+            balance_override.mint,
+            config.mint,
+            ErrorCode::BalanceOverrideMismatch
+        );
+        require_keys_eq!(
+            balance_override.owner,
+            owner,
+            ErrorCode::BalanceOverrideMismatch
+        );
+        balance_override.max_balance
+    } else {
+        config.max_balance
+    };
+
+    // Token-2022 runs the hook after moving the tokens, so this is the new balance
+    require!(
+        cap == 0 || ctx.accounts.destination_token.amount <= cap,
+        ErrorCode::BalanceCapExceeded
+    );
+    Ok(())
+}
+
+// Count `amount` against the source owner's rolling velocity window, using the
+// limit for `tier` (the sender's bouncer leaf tier, 0 when not verified)
+pub fn record_velocity(
+    ctx: &Context<TransferHook>,
+    config: &MintConfig,
+    owner: Pubkey,
+    amount: u64,
+    tier: u16,
+) -> Result<()> {
+    let limit = config.velocity_limit_for(tier);
+    if config.velocity_window == 0 || limit == 0 {
+        return Ok(());
+    }
+
+    let tracker_info = ctx.accounts.transfer_tracker.to_account_info();
+    require!(
+        *tracker_info.owner == crate::ID && !tracker_info.data_is_empty(),
+        ErrorCode::MissingTransferTracker
+    );
+    let mut tracker = {
+        let data = tracker_info.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        TransferTracker::try_deserialize(&mut data_slice)
+            .map_err(|_| error!(ErrorCode::TransferTrackerMismatch))?
+    };
+    require_keys_eq!(tracker.mint, config.mint, ErrorCode::TransferTrackerMismatch);
+    require_keys_eq!(tracker.owner, owner, ErrorCode::TransferTrackerMismatch);
+
+    let now = Clock::get()?.unix_timestamp;
+    let sent = tracker.roll(config.velocity_window, now);
+    let total = sent
+        .checked_add(amount)
+        .ok_or(ErrorCode::VelocityLimitExceeded)?;
+    require!(total <= limit, ErrorCode::VelocityLimitExceeded);
+    tracker.record(amount);
+    let mut data = tracker_info.try_borrow_mut_data()?;
+    tracker.try_serialize(&mut &mut data[..])
+}
+
+// Which side of the transfer a subject is checked for
+#[derive(Clone, Copy)]
+pub enum Side {
+    Recipient,
+    Sender,
+}
+
+// Returns the tier of the leaf the bouncer verified for `key`, 0 when there is none
+pub fn check_whitelist<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, TransferHook<'info>>,
+    config: &MintConfig,
+    key: Pubkey,
+    side: Side,
+) -> Result<u16> {
     // Validate that bouncer_program and bouncer_list match config
     require_keys_eq!(
         ctx.accounts.bouncer_program.key(),
-        ctx.accounts.config.bouncer_program_id,
-        ErrorCode::TransferNotAllowed
+        config.bouncer_program_id,
+        ErrorCode::ConfigMismatch
     );
     require_keys_eq!(
         ctx.accounts.bouncer_list.key(),
-        ctx.accounts.config.bouncer_list,
-        ErrorCode::TransferNotAllowed
+        config.bouncer_list,
+        ErrorCode::ConfigMismatch
     );
     // Ensure bouncer_program is executable
     require!(
         ctx.accounts.bouncer_program.executable,
-        ErrorCode::TransferNotAllowed
+        ErrorCode::ConfigMismatch
     );
-    
+
+    let party_account = match side {
+        Side::Recipient => ctx.accounts.entry_account.to_account_info(),
+        Side::Sender => ctx.accounts.source_entry_account.to_account_info(),
+    };
+
+    let (proof, leaf, aux_account) = match list_target(&ctx.accounts.bouncer_list)? {
+        ListTarget::Group {
+            combinator,
+            members,
+        } => {
+            check_group(ctx, key, side, combinator, &members)?;
+            return Ok(0);
+        }
+        // The entry PDA is the only account the bouncer needs
+        ListTarget::Direct => (Vec::new(), None, party_account),
+        // The proof comes from the party's ProofBuffer and is checked against
+        // the list's RootHistory / CompressedTree
+        ListTarget::Merkle { .. } => {
+            let (proof, leaf) = read_proof_buffer(
+                &party_account,
+                &ctx.accounts.bouncer_program.key(),
+                &ctx.accounts.bouncer_list.key(),
+                &key,
+            )?;
+            let aux_account = ctx
+                .remaining_accounts
+                .first()
+                .ok_or(ErrorCode::InvalidConfig)?
+                .clone();
+            (proof, leaf, aux_account)
+        }
+    };
+
     let mut cpi_ctx = CpiContext::new(
         ctx.accounts.bouncer_program.to_account_info(),
-        bouncer::cpi::accounts::AssertAllowed {
+        bouncer::cpi::accounts::CheckStatus {
             list: ctx.accounts.bouncer_list.to_account_info(),
         },
     );
 
-    // Add entry_account as remaining account
-    cpi_ctx = cpi_ctx.with_remaining_accounts(vec![ctx.accounts.entry_account.to_account_info()]);
-    
-    // Call bouncer and convert any error to TransferNotAllowed
-    bouncer::cpi::assert_allowed(cpi_ctx, key, Vec::new())
-        .map_err(|_| ErrorCode::TransferNotAllowed)?;
-    
-    Ok(())
+    // Add the entry / list account as remaining account
+    cpi_ctx = cpi_ctx.with_remaining_accounts(vec![aux_account]);
+
+    // check_status reports denials as return data rather than failing, so the
+    // reason survives the CPI and can be turned into a specific error here
+    let tier = leaf.as_ref().map_or(0, |leaf| leaf.tier);
+    let verdict = bouncer::cpi::check_status(cpi_ctx, key, proof, leaf)?.get();
+    verdict_result(&verdict, side)?;
+    Ok(tier)
+}
+
+// Proof (and leaf metadata) `subject` stored in its bouncer ProofBuffer for `list`
+fn read_proof_buffer(
+    proof_buffer: &AccountInfo,
+    bouncer_program: &Pubkey,
+    list: &Pubkey,
+    subject: &Pubkey,
+) -> Result<(Vec<u8>, Option<LeafData>)> {
+    require!(
+        proof_buffer.owner == bouncer_program && !proof_buffer.data_is_empty(),
+        ErrorCode::MissingProof
+    );
+
+    let data = proof_buffer.try_borrow_data()?;
+    let mut data_slice: &[u8] = &data;
+    let buffer = ProofBuffer::try_deserialize(&mut data_slice)
+        .map_err(|_| error!(ErrorCode::EntryAccountMismatch))?;
+    require_keys_eq!(buffer.list, *list, ErrorCode::EntryAccountMismatch);
+    require_keys_eq!(buffer.subject, *subject, ErrorCode::EntryAccountMismatch);
+
+    Ok((buffer.proof, buffer.leaf))
+}
+
+fn verdict_result(verdict: &Verdict, side: Side) -> Result<()> {
+    if verdict.allowed {
+        return Ok(());
+    }
+    msg!("bouncer denied: reason {}", verdict.reason);
+
+    let error = match (verdict.reason, side) {
+        (REASON_BLOCKED, Side::Recipient) => ErrorCode::RecipientBlocklisted,
+        (REASON_BLOCKED, Side::Sender) => ErrorCode::SenderBlocklisted,
+        (REASON_NOT_LISTED | REASON_OUTSIDE_VALIDITY | REASON_TIER_TOO_LOW, Side::Recipient) => {
+            ErrorCode::RecipientNotAllowlisted
+        }
+        (REASON_NOT_LISTED | REASON_OUTSIDE_VALIDITY | REASON_TIER_TOO_LOW, Side::Sender) => {
+            ErrorCode::SenderNotAllowlisted
+        }
+        (REASON_ENTRY_MISMATCH, _) => ErrorCode::EntryAccountMismatch,
+        (REASON_INVALID_PROOF, _) => ErrorCode::ProofInvalid,
+        (REASON_INVALID_LIST, _) => ErrorCode::ConfigMismatch,
+        _ => ErrorCode::TransferNotAllowed,
+    };
+    Err(error.into())
 }
 
+// Evaluate a ListGroup: check_status each member list with this side's entry
+// PDA, taken from the (list, destination entry, source entry) triples, and
+// combine the verdicts the way assert_allowed_group does. A denial is reported
+// through verdict_result: the first failing member for AND, the first member
+// for OR when none passes.
+fn check_group<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, TransferHook<'info>>,
+    key: Pubkey,
+    side: Side,
+    combinator: u8,
+    members: &[Pubkey],
+) -> Result<()> {
+    require!(
+        combinator == COMBINATOR_AND || combinator == COMBINATOR_OR,
+        ErrorCode::InvalidConfig
+    );
+    require!(
+        ctx.remaining_accounts.len() >= members.len() * GROUP_MEMBER_STRIDE,
+        ErrorCode::InvalidConfig
+    );
+
+    let entry_offset = match side {
+        Side::Recipient => 1,
+        Side::Sender => 2,
+    };
+    let mut first_denial = None;
+    for (member_key, member) in members
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(GROUP_MEMBER_STRIDE))
+    {
+        require_keys_eq!(member[0].key(), *member_key, ErrorCode::ConfigMismatch);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.bouncer_program.to_account_info(),
+            bouncer::cpi::accounts::CheckStatus {
+                list: member[0].clone(),
+            },
+        )
+        .with_remaining_accounts(vec![member[entry_offset].clone()]);
+        let verdict = bouncer::cpi::check_status(cpi_ctx, key, Vec::new(), None)?.get();
+
+        if combinator == COMBINATOR_AND {
+            verdict_result(&verdict, side)?;
+        } else if verdict.allowed {
+            return Ok(());
+        } else if first_denial.is_none() {
+            first_denial = Some(verdict);
+        }
+    }
+
+    if combinator == COMBINATOR_OR {
+        return match first_denial {
+            Some(verdict) => verdict_result(&verdict, side),
+            None => err!(ErrorCode::TransferNotAllowed),
+        };
+    }
+    Ok(())
+}
 
 // Helper to convert spl_tlv_account_resolution::solana_program_error::ProgramError to Anchor Error
 pub fn to_anchor_error_tlv(err: spl_tlv_account_resolution::solana_program_error::ProgramError) -> Error {
@@ -50,5 +644,5 @@ pub fn to_anchor_error_tlv(err: spl_tlv_account_resolution::solana_program_error
         }
         _ => solana_program::program_error::ProgramError::InvalidInstructionData,
     };
-    Error::from(ProgramError::from(solana_err))
+    Error::from(solana_err)
 }
\ No newline at end of file
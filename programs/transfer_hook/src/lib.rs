@@ -1,8 +1,8 @@
 use anchor_lang::{
     prelude::*,
-    system_program::{create_account, CreateAccount},
+    system_program::{create_account, transfer, CreateAccount, Transfer},
 };
-use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
 use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
 
 mod account_structs;
@@ -11,6 +11,9 @@ mod state;
 
 use account_structs::*;
 use helpers::*;
+use state::{
+    TierLimit, CHECK_BOTH, CHECK_RECIPIENT, CHECK_SENDER, MAX_TIER_LIMITS, MAX_VELOCITY_WINDOW,
+};
 
 #[error_code]
 pub enum ErrorCode {
@@ -18,6 +21,54 @@ pub enum ErrorCode {
     TransferNotAllowed,
     #[msg("Custom error")]
     Custom,
+    #[msg("Config account does not match mint")]
+    InvalidConfig,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid check mode value")]
+    InvalidCheckMode,
+    #[msg("Hook can only be invoked during a Token-2022 transfer")]
+    NotTransferring,
+    #[msg("Mint transfer hook program does not match this program")]
+    InvalidTransferHookProgram,
+    #[msg("Recipient is not on the allowlist")]
+    RecipientNotAllowlisted,
+    #[msg("Recipient is blocklisted")]
+    RecipientBlocklisted,
+    #[msg("Sender is not on the allowlist")]
+    SenderNotAllowlisted,
+    #[msg("Sender is blocklisted")]
+    SenderBlocklisted,
+    #[msg("Bouncer entry account does not match the transfer party")]
+    EntryAccountMismatch,
+    #[msg("Bouncer program or list does not match the hook config")]
+    ConfigMismatch,
+    #[msg("Bouncer proof is invalid")]
+    ProofInvalid,
+    #[msg("Transfer party has not written a bouncer proof buffer")]
+    MissingProof,
+    #[msg("Invalid amount policy")]
+    InvalidAmountPolicy,
+    #[msg("Transfer amount is below the minimum")]
+    AmountBelowMinimum,
+    #[msg("Transfer amount is above the maximum")]
+    AmountAboveMaximum,
+    #[msg("Transfer amount is not a multiple of the lot size")]
+    AmountNotLotMultiple,
+    #[msg("Invalid velocity policy")]
+    InvalidVelocityPolicy,
+    #[msg("Sender has no transfer tracker for this mint")]
+    MissingTransferTracker,
+    #[msg("Transfer tracker does not match mint or sender")]
+    TransferTrackerMismatch,
+    #[msg("Transfer exceeds the sender's velocity limit")]
+    VelocityLimitExceeded,
+    #[msg("Balance override does not match mint or recipient")]
+    BalanceOverrideMismatch,
+    #[msg("Transfer would take the recipient over the maximum balance")]
+    BalanceCapExceeded,
+    #[msg("List group members must be direct-PDA lists")]
+    UnsupportedGroupMember,
 }
 
 declare_id!("EdB4jakxsXGit5ojRshNv2bgfNNKgo6zqM5FEWiNLvtR");
@@ -29,61 +80,32 @@ pub mod market_transfer_hook {
     // ------------------------------------------------------------
     // Initialize ExtraAccountMetaList (ONCE per mint)
     // ------------------------------------------------------------
+    // For a ListGroup, pass each member List in remaining_accounts (group
+    // order); both meta-list instructions reject non direct-PDA members.
     pub fn initialize_extra_account_meta_list(
         ctx: Context<InitializeExtraAccountMetaList>,
     ) -> Result<()> {
-        // index 0-3 are the accounts required for token transfer (source, mint, destination, owner)
-        // index 4 is address of ExtraAccountMetaList account
-        // index 5 is address of Config account
-        // index 6 is address of Bouncer list
-        // index 7 is address of Token program
-        let metas = vec![
-            //index 5 = config account
-            ExtraAccountMeta::new_with_pubkey(
-                &spl_tlv_account_resolution::solana_pubkey::Pubkey::new_from_array(
-                    ctx.accounts.config.key().to_bytes(),
-                ),
-                false,
-                false,
-            )
-            .map_err(to_anchor_error_tlv)?,
-            // index 6 = bouncer_list
-            ExtraAccountMeta::new_with_pubkey(
-                &spl_tlv_account_resolution::solana_pubkey::Pubkey::new_from_array(
-                    ctx.accounts.bouncer_list.key().to_bytes(),
-                ),
-                false,
-                false,
-            )
-            .map_err(to_anchor_error_tlv)?,
-            // index 7 = bouncer_program
-            ExtraAccountMeta::new_with_pubkey(
-                &spl_tlv_account_resolution::solana_pubkey::Pubkey::new_from_array(
-                    ctx.accounts.bouncer_program.key().to_bytes(),
-                ),
-                false,
-                false,
-            )
-            .map_err(to_anchor_error_tlv)?,
-            // index 8 = entry_account
-            ExtraAccountMeta::new_with_pubkey(
-                &spl_tlv_account_resolution::solana_pubkey::Pubkey::new_from_array(
-                    ctx.accounts.entry_account.key().to_bytes(),
-                ),
-                false,
-                false,
-            )
-            .map_err(to_anchor_error_tlv)?,
-            // index 9 = token_program
-            ExtraAccountMeta::new_with_pubkey(
-                &spl_tlv_account_resolution::solana_pubkey::Pubkey::new_from_array(
-                    ctx.accounts.token_program.key().to_bytes(),
-                ),
-                false,
-                false,
-            )
-            .map_err(to_anchor_error_tlv)?,
-        ];
+        // Validate the config this mint resolves to (MintConfig or legacy global Config)
+        require_own_config(&ctx.accounts.config, &ctx.accounts.mint_config)?;
+        let config = resolve_config(&ctx.accounts.config, &ctx.accounts.mint.key())?;
+        require_keys_eq!(
+            ctx.accounts.bouncer_program.key(),
+            config.bouncer_program_id,
+            ErrorCode::InvalidConfig
+        );
+        require_keys_eq!(
+            ctx.accounts.bouncer_list.key(),
+            config.bouncer_list,
+            ErrorCode::InvalidConfig
+        );
+
+        let metas = build_extra_account_metas(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.bouncer_list.key(),
+            &ctx.accounts.bouncer_program.key(),
+            &ctx.accounts.token_program.key(),
+            &meta_list_target(&ctx.accounts.bouncer_list, ctx.remaining_accounts)?,
+        )?;
 
         let size = ExtraAccountMetaList::size_of(metas.len()).map_err(to_anchor_error_tlv)? as u64;
         let lamports = Rent::get()?.minimum_balance(size as usize);
@@ -118,16 +140,100 @@ pub mod market_transfer_hook {
         Ok(())
     }
 
+    // ------------------------------------------------------------
+    // Rewrite the ExtraAccountMetaList (config admin only), e.g. to
+    // migrate mints initialized with an older account layout
+    // ------------------------------------------------------------
+    pub fn update_extra_account_meta_list(
+        ctx: Context<UpdateExtraAccountMetaList>,
+    ) -> Result<()> {
+        require_own_config(&ctx.accounts.config, &ctx.accounts.mint_config)?;
+        // Keep the config the list already points at; the only switch allowed is
+        // from the global Config to the mint's own MintConfig
+        let stored_config = stored_config_key(&ctx.accounts.extra_account_meta_list)?;
+        require!(
+            ctx.accounts.config.key() == stored_config
+                || ctx.accounts.config.key() == ctx.accounts.mint_config.key(),
+            ErrorCode::InvalidConfig
+        );
+        let config = resolve_config(&ctx.accounts.config, &ctx.accounts.mint.key())?;
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            config.admin,
+            ErrorCode::Unauthorized
+        );
+        require_keys_eq!(
+            ctx.accounts.bouncer_program.key(),
+            config.bouncer_program_id,
+            ErrorCode::InvalidConfig
+        );
+        require_keys_eq!(
+            ctx.accounts.bouncer_list.key(),
+            config.bouncer_list,
+            ErrorCode::InvalidConfig
+        );
+
+        let metas = build_extra_account_metas(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.bouncer_list.key(),
+            &ctx.accounts.bouncer_program.key(),
+            &ctx.accounts.token_program.key(),
+            &meta_list_target(&ctx.accounts.bouncer_list, ctx.remaining_accounts)?,
+        )?;
+
+        let size = ExtraAccountMetaList::size_of(metas.len()).map_err(to_anchor_error_tlv)?;
+        let meta_list_info = ctx.accounts.extra_account_meta_list.to_account_info();
+
+        // Top up rent and grow the account if the new list is larger
+        if size > meta_list_info.data_len() {
+            let lamports = Rent::get()?
+                .minimum_balance(size)
+                .saturating_sub(meta_list_info.lamports());
+            if lamports > 0 {
+                transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.admin.to_account_info(),
+                            to: meta_list_info.clone(),
+                        },
+                    ),
+                    lamports,
+                )?;
+            }
+            meta_list_info.resize(size)?;
+        }
+
+        ExtraAccountMetaList::update::<ExecuteInstruction>(
+            &mut meta_list_info.try_borrow_mut_data()?,
+            &metas,
+        )
+        .map_err(to_anchor_error_tlv)?;
+
+        Ok(())
+    }
+
     // ------------------------------------------------------------
     // Transfer hook (called on every transfer / transfer_checked)
     // ------------------------------------------------------------
-    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+    pub fn transfer_hook<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        // Reject direct calls: only valid inside a Token-2022 transfer of a mint using this hook
+        assert_is_transferring(&ctx)?;
+
+        let config = resolve_config(&ctx.accounts.config, &ctx.accounts.mint.key())?;
         let src_owner = ctx.accounts.source_token.owner;
         let dst_owner = ctx.accounts.destination_token.owner;
 
         msg!("src_owner: {}", src_owner.to_string());
         msg!("dst_owner: {}", dst_owner.to_string());
         msg!("entry_account: {}", ctx.accounts.entry_account.key().to_string());
+        msg!(
+            "source_entry_account: {}",
+            ctx.accounts.source_entry_account.key().to_string()
+        );
         msg!(
             "source_token: {}",
             ctx.accounts.source_token.key().to_string()
@@ -150,8 +256,20 @@ pub mod market_transfer_hook {
             ctx.accounts.bouncer_list.key().to_string()
         );
 
-        // Check if destination is whitelisted
-        check_whitelist(&ctx, dst_owner)?;
+        check_amount(&config, amount)?;
+
+        // Check the destination and/or source owner, depending on the mint's check mode
+        if checks_recipient(&config) {
+            check_whitelist(&ctx, &config, dst_owner, Side::Recipient)?;
+        }
+        // The sender's tier only counts once the bouncer has verified its leaf
+        let mut sender_tier = 0;
+        if checks_sender(&config) {
+            sender_tier = check_whitelist(&ctx, &config, src_owner, Side::Sender)?;
+        }
+
+        check_balance(&ctx, &config, dst_owner)?;
+        record_velocity(&ctx, &config, src_owner, amount, sender_tier)?;
 
         Ok(())
     }
@@ -175,9 +293,7 @@ pub mod market_transfer_hook {
     }
 
 
-    //TODO - Need to add the assert from docs so that this is called only through a transfer_checked instruction
-    //and not directly
-
+    // Create the global config (program upgrade authority only)
     pub fn initialize_config(
         ctx: Context<InitializeConfig>,
         bouncer_program_id: Pubkey,
@@ -187,6 +303,8 @@ pub mod market_transfer_hook {
         config.bouncer_program_id = bouncer_program_id;
         config.bouncer_list = bouncer_list;
         config.bump = ctx.bumps.config;
+        config.admin = ctx.accounts.payer.key();
+        config.pending_admin = Pubkey::default();
         Ok(())
     }
 
@@ -205,42 +323,218 @@ pub mod market_transfer_hook {
         Ok(())
     }
 
-    pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
-        // Clear the account data and transfer excess lamports to payer
-        // Keep only the rent-exempt amount so account remains valid
-        // Then init_if_needed can reinitialize it with new structure
+    // Two-step admin handover: the current admin proposes, the new admin accepts.
+    // Proposing Pubkey::default() cancels a pending handover.
+    pub fn transfer_config_admin(
+        ctx: Context<TransferConfigAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.config.pending_admin = new_admin;
+        Ok(())
+    }
+
+    pub fn accept_config_admin(ctx: Context<AcceptConfigAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = config.pending_admin;
+        config.pending_admin = Pubkey::default();
+        Ok(())
+    }
+
+    // Close the global config and refund its rent
+    pub fn close_config(_ctx: Context<CloseConfig>) -> Result<()> {
+        Ok(())
+    }
+
+    // ------------------------------------------------------------
+    // Grow a global Config created before it stored an admin and set
+    // that admin (program upgrade authority only)
+    // ------------------------------------------------------------
+    pub fn migrate_config(ctx: Context<MigrateConfig>, admin: Pubkey) -> Result<()> {
         let config_info = ctx.accounts.config.to_account_info();
-        let payer_info = ctx.accounts.payer.to_account_info();
-
-        // Calculate rent-exempt amount for the account size
-        let account_size = config_info.data_len();
-        let rent = Rent::get()?;
-        let rent_exempt_amount = rent.minimum_balance(account_size);
-
-        // Get current lamports
-        let current_lamports = config_info.lamports();
-
-        // Calculate excess lamports to transfer
-        let excess_lamports = current_lamports
-            .checked_sub(rent_exempt_amount)
-            .unwrap_or(0);
-
-        // Transfer excess lamports to payer
-        if excess_lamports > 0 {
-            **config_info.lamports.borrow_mut() = rent_exempt_amount;
-            **payer_info.lamports.borrow_mut() = payer_info
-                .lamports()
-                .checked_add(excess_lamports)
-                .ok_or(ErrorCode::Custom)?;
+        require!(
+            config_info.data_len() == state::Config::LEGACY_LEN,
+            ErrorCode::InvalidConfig
+        );
+        let legacy = read_config(&config_info.try_borrow_data()?)?;
+
+        let size = 8 + state::Config::INIT_SPACE;
+        let lamports = Rent::get()?
+            .minimum_balance(size)
+            .saturating_sub(config_info.lamports());
+        if lamports > 0 {
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: config_info.clone(),
+                    },
+                ),
+                lamports,
+            )?;
         }
+        config_info.resize(size)?;
 
-        // Clear the account data (set to all zeros)
-        // This removes the discriminator, allowing init_if_needed to reinitialize
+        let config = state::Config {
+            admin,
+            pending_admin: Pubkey::default(),
+            ..legacy
+        };
         let mut data = config_info.try_borrow_mut_data()?;
-        data.fill(0);
+        config.try_serialize(&mut &mut data[..])
+    }
+
+    pub fn initialize_mint_config(
+        ctx: Context<InitializeMintConfig>,
+        bouncer_program_id: Pubkey,
+        bouncer_list: Pubkey,
+    ) -> Result<()> {
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.mint = ctx.accounts.mint.key();
+        mint_config.bouncer_program_id = bouncer_program_id;
+        mint_config.bouncer_list = bouncer_list;
+        mint_config.bump = ctx.bumps.mint_config;
+        mint_config.admin = ctx.accounts.mint_authority.key();
+        mint_config.pending_admin = Pubkey::default();
+        mint_config.check_mode = CHECK_RECIPIENT;
+        mint_config.min_amount = 0;
+        mint_config.max_amount = 0;
+        mint_config.lot_size = 0;
+        mint_config.velocity_window = 0;
+        mint_config.velocity_limit = 0;
+        mint_config.velocity_tier_limits = Vec::new();
+        mint_config.max_balance = 0;
+        Ok(())
+    }
+
+    pub fn update_mint_config(
+        ctx: Context<UpdateMintConfig>,
+        bouncer_program_id: Option<Pubkey>,
+        bouncer_list: Option<Pubkey>,
+        check_mode: Option<u8>,
+    ) -> Result<()> {
+        let mint_config = &mut ctx.accounts.mint_config;
+        if let Some(bouncer_program_id) = bouncer_program_id {
+            mint_config.bouncer_program_id = bouncer_program_id;
+        }
+        if let Some(bouncer_list) = bouncer_list {
+            mint_config.bouncer_list = bouncer_list;
+        }
+        if let Some(check_mode) = check_mode {
+            require!(
+                check_mode == CHECK_RECIPIENT
+                    || check_mode == CHECK_SENDER
+                    || check_mode == CHECK_BOTH,
+                ErrorCode::InvalidCheckMode
+            );
+            mint_config.check_mode = check_mode;
+        }
+        Ok(())
+    }
+
+    // Per-transfer min / max amount and lot size (0 = no limit)
+    pub fn set_amount_policy(
+        ctx: Context<SetAmountPolicy>,
+        min_amount: u64,
+        max_amount: u64,
+        lot_size: u64,
+    ) -> Result<()> {
+        require!(
+            max_amount == 0 || min_amount <= max_amount,
+            ErrorCode::InvalidAmountPolicy
+        );
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.min_amount = min_amount;
+        mint_config.max_amount = max_amount;
+        mint_config.lot_size = lot_size;
+        Ok(())
+    }
+
+    // Cap what each holder may send per rolling `window` seconds, with
+    // optional per-tier overrides of `limit`. A window of 0 disables the cap;
+    // a limit of 0 leaves the holders it applies to uncapped
+    pub fn set_velocity_policy(
+        ctx: Context<SetVelocityPolicy>,
+        window: i64,
+        limit: u64,
+        tier_limits: Vec<TierLimit>,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_VELOCITY_WINDOW).contains(&window),
+            ErrorCode::InvalidVelocityPolicy
+        );
+        require!(
+            tier_limits.len() <= MAX_TIER_LIMITS,
+            ErrorCode::InvalidVelocityPolicy
+        );
+        for (i, tier_limit) in tier_limits.iter().enumerate() {
+            require!(
+                tier_limits[..i].iter().all(|t| t.tier != tier_limit.tier),
+                ErrorCode::InvalidVelocityPolicy
+            );
+        }
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.velocity_window = window;
+        mint_config.velocity_limit = limit;
+        mint_config.velocity_tier_limits = tier_limits;
+        Ok(())
+    }
+
+    // Cap on any one holder's balance (0 = no cap)
+    pub fn set_max_balance(ctx: Context<SetMaxBalance>, max_balance: u64) -> Result<()> {
+        ctx.accounts.mint_config.max_balance = max_balance;
+        Ok(())
+    }
+
+    // Replace the mint's max balance for `owner` (0 = exempt from the cap)
+    pub fn set_balance_override(
+        ctx: Context<SetBalanceOverride>,
+        owner: Pubkey,
+        max_balance: u64,
+    ) -> Result<()> {
+        let balance_override = &mut ctx.accounts.balance_override;
+        balance_override.mint = ctx.accounts.mint_config.mint;
+        balance_override.owner = owner;
+        balance_override.bump = ctx.bumps.balance_override;
+        balance_override.max_balance = max_balance;
+        Ok(())
+    }
+
+    pub fn remove_balance_override(_ctx: Context<RemoveBalanceOverride>) -> Result<()> {
+        Ok(())
+    }
+
+    // ------------------------------------------------------------
+    // Create a holder's TransferTracker (anyone may pay); required
+    // before the holder can send a mint with a velocity limit
+    // ------------------------------------------------------------
+    pub fn initialize_transfer_tracker(
+        ctx: Context<InitializeTransferTracker>,
+        owner: Pubkey,
+    ) -> Result<()> {
+        let tracker = &mut ctx.accounts.transfer_tracker;
+        tracker.mint = ctx.accounts.mint.key();
+        tracker.owner = owner;
+        tracker.bump = ctx.bumps.transfer_tracker;
+        Ok(())
+    }
+
+    pub fn transfer_mint_config_admin(
+        ctx: Context<TransferMintConfigAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.mint_config.pending_admin = new_admin;
+        Ok(())
+    }
+
+    pub fn accept_mint_config_admin(ctx: Context<AcceptMintConfigAdmin>) -> Result<()> {
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.admin = mint_config.pending_admin;
+        mint_config.pending_admin = Pubkey::default();
+        Ok(())
+    }
 
-        // Account now has zeroed data but still has rent-exempt lamports
-        // init_if_needed will see no discriminator and reinitialize it
+    pub fn close_mint_config(_ctx: Context<CloseMintConfig>) -> Result<()> {
         Ok(())
     }
 }
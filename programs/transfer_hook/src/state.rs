@@ -1,9 +1,221 @@
 use anchor_lang::prelude::*;
 
+// Which side(s) of a transfer are checked against the bouncer list
+pub const CHECK_RECIPIENT: u8 = 0;
+pub const CHECK_SENDER: u8 = 1;
+pub const CHECK_BOTH: u8 = 2;
+
+// Velocity windows are tracked in this many equal buckets, so the window
+// rolls forward one bucket at a time
+pub const TRACKER_BUCKETS: usize = 24;
+// Longest velocity window a mint may set, in seconds (366 days)
+pub const MAX_VELOCITY_WINDOW: i64 = 366 * 24 * 60 * 60;
+// Per-tier velocity overrides a MintConfig can hold
+pub const MAX_TIER_LIMITS: usize = 4;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
     pub bouncer_program_id: Pubkey,
     pub bouncer_list: Pubkey,
     pub bump: u8,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey, // Pubkey::default() when no handover is in progress
+}
+
+impl Config {
+    /// Size of a global config created before `admin` and `pending_admin`
+    /// existed. Such accounts are read with no admin until `migrate_config`.
+    pub const LEGACY_LEN: usize = 8 + 32 + 32 + 1;
+}
+
+/// Field layout of a `Config` of `Config::LEGACY_LEN` bytes, after the discriminator.
+#[derive(AnchorDeserialize)]
+pub struct LegacyConfig {
+    pub bouncer_program_id: Pubkey,
+    pub bouncer_list: Pubkey,
+    pub bump: u8,
+}
+
+/// Per-mint hook configuration at `["config", mint]`. Takes precedence over
+/// the global `Config` when the mint's ExtraAccountMetaList points at it.
+#[account]
+#[derive(InitSpace)]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    pub bouncer_program_id: Pubkey,
+    pub bouncer_list: Pubkey,
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey, // Pubkey::default() when no handover is in progress
+    pub check_mode: u8,        // 0=Recipient, 1=Sender, 2=Both
+    // Per-transfer amount policy, in base units; 0 disables each limit
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub lot_size: u64, // amount must be a multiple of this
+    // Rolling-window velocity limit per sending holder; 0 seconds disables it,
+    // as does a limit of 0
+    pub velocity_window: i64,
+    pub velocity_limit: u64,
+    #[max_len(MAX_TIER_LIMITS)]
+    pub velocity_tier_limits: Vec<TierLimit>, // overrides velocity_limit by bouncer leaf tier
+    // Largest balance one holder may reach by receiving; 0 disables the cap
+    pub max_balance: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct TierLimit {
+    pub tier: u16,
+    pub limit: u64,
+}
+
+impl MintConfig {
+    /// Amount a holder of `tier` may send per velocity window; 0 = no cap.
+    pub fn velocity_limit_for(&self, tier: u16) -> u64 {
+        self.velocity_tier_limits
+            .iter()
+            .find(|t| t.tier == tier)
+            .map_or(self.velocity_limit, |t| t.limit)
+    }
+}
+
+/// Admin-set replacement for the mint's `max_balance` for one holder, at
+/// `["balance", mint, owner]`.
+#[account]
+#[derive(InitSpace)]
+pub struct BalanceOverride {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub max_balance: u64, // 0 = holder is exempt from the cap
+}
+
+/// Amounts a holder sent of `mint`, at `["tracker", mint, owner]`. Written by
+/// `transfer_hook` on every transfer out while the mint has a velocity limit.
+#[account]
+#[derive(InitSpace)]
+pub struct TransferTracker {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub window: i64,      // velocity_window the buckets were filled under
+    pub last_bucket: i64, // index (unix time / bucket length) of the newest bucket
+    pub buckets: [u64; TRACKER_BUCKETS],
+}
+
+impl TransferTracker {
+    fn bucket_len(window: i64) -> i64 {
+        (window + TRACKER_BUCKETS as i64 - 1) / TRACKER_BUCKETS as i64
+    }
+
+    /// Drop buckets that have left the window ending at `now`, then return
+    /// the amount still inside it.
+    pub fn roll(&mut self, window: i64, now: i64) -> u64 {
+        let current = now.div_euclid(Self::bucket_len(window));
+        if self.window != window || current - self.last_bucket >= TRACKER_BUCKETS as i64 {
+            self.window = window;
+            self.buckets = [0; TRACKER_BUCKETS];
+        } else {
+            for bucket in self.last_bucket + 1..=current {
+                self.buckets[bucket as usize % TRACKER_BUCKETS] = 0;
+            }
+        }
+        // A clock that steps back keeps filling the newest bucket
+        self.last_bucket = self.last_bucket.max(current);
+        self.buckets.iter().fold(0u64, |sum, b| sum.saturating_add(*b))
+    }
+
+    pub fn record(&mut self, amount: u64) {
+        let bucket = &mut self.buckets[self.last_bucket as usize % TRACKER_BUCKETS];
+        *bucket = bucket.saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 100-second buckets
+    const WINDOW: i64 = 100 * TRACKER_BUCKETS as i64;
+
+    fn new_tracker() -> TransferTracker {
+        TransferTracker {
+            mint: Pubkey::default(),
+            owner: Pubkey::default(),
+            bump: 0,
+            window: 0,
+            last_bucket: 0,
+            buckets: [0; TRACKER_BUCKETS],
+        }
+    }
+
+    fn spend(tracker: &mut TransferTracker, now: i64, amount: u64) -> u64 {
+        let sent = tracker.roll(WINDOW, now);
+        tracker.record(amount);
+        sent
+    }
+
+    #[test]
+    fn sums_spending_inside_the_window() {
+        let mut tracker = new_tracker();
+        assert_eq!(spend(&mut tracker, 1_000_000, 10), 0);
+        assert_eq!(spend(&mut tracker, 1_000_050, 5), 10);
+        assert_eq!(spend(&mut tracker, 1_000_700, 1), 15);
+        assert_eq!(tracker.roll(WINDOW, 1_000_000 + WINDOW - 1), 16);
+    }
+
+    #[test]
+    fn expires_buckets_as_time_advances() {
+        let mut tracker = new_tracker();
+        spend(&mut tracker, 1_000_000, 10);
+        spend(&mut tracker, 1_000_100, 5);
+        spend(&mut tracker, 1_000_200, 1);
+
+        // One bucket later the first spend has left the window
+        assert_eq!(tracker.roll(WINDOW, 1_000_000 + WINDOW), 6);
+        assert_eq!(tracker.roll(WINDOW, 1_000_100 + WINDOW), 1);
+        assert_eq!(tracker.roll(WINDOW, 1_000_200 + WINDOW), 0);
+    }
+
+    #[test]
+    fn resets_after_a_gap_of_a_whole_window() {
+        let mut tracker = new_tracker();
+        spend(&mut tracker, 1_000_000, 10);
+        spend(&mut tracker, 1_000_100, 5);
+
+        assert_eq!(tracker.roll(WINDOW, 1_000_100 + WINDOW), 0);
+        assert_eq!(tracker.buckets, [0; TRACKER_BUCKETS]);
+
+        // Far beyond the window, with a different ring position
+        spend(&mut tracker, 1_000_100 + WINDOW, 7);
+        assert_eq!(tracker.roll(WINDOW, 1_000_100 + 1_000 * WINDOW + 300), 0);
+        assert_eq!(tracker.buckets, [0; TRACKER_BUCKETS]);
+    }
+
+    #[test]
+    fn clock_stepping_back_keeps_filling_the_newest_bucket() {
+        let mut tracker = new_tracker();
+        spend(&mut tracker, 1_000_500, 10);
+        let newest = tracker.last_bucket;
+
+        assert_eq!(spend(&mut tracker, 1_000_200, 5), 10);
+        assert_eq!(tracker.last_bucket, newest);
+        assert_eq!(tracker.buckets[newest as usize % TRACKER_BUCKETS], 15);
+
+        // Both spends expire together with the newest bucket
+        assert_eq!(tracker.roll(WINDOW, 1_000_500 + WINDOW - 1), 15);
+        assert_eq!(tracker.roll(WINDOW, 1_000_500 + WINDOW), 0);
+    }
+
+    #[test]
+    fn window_change_resets_the_buckets() {
+        let mut tracker = new_tracker();
+        spend(&mut tracker, 1_000_000, 10);
+        assert_eq!(tracker.roll(WINDOW, 1_000_050), 10);
+
+        assert_eq!(tracker.roll(WINDOW * 2, 1_000_050), 0);
+        assert_eq!(tracker.window, WINDOW * 2);
+        tracker.record(3);
+        assert_eq!(tracker.roll(WINDOW * 2, 1_000_060), 3);
+    }
 }
@@ -56,28 +56,13 @@ import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
   // Bouncer list PDA (the whitelist)
   const BOUNCER_LIST = new PublicKey(process.env.BOUNCER_LIST ?? "7h7qtpFwNNgYPK68b9abbomcUoBcTVvmWC21TQWsQVn9");
 
-  // Example whitelisted PDA (used to derive entry account for initialization)
-  // This should be a PDA that exists in the whitelist
-  const EXAMPLE_WHITELISTED_PDA = new PublicKey(process.env.WHITELISTED_PDA ?? "J8ridcz8pgJ4g9E5sk7xmjDjWD3AR5rMqXUxkrc8Zp9L");
-
-  // Helper function to derive entry PDA
-  function entryPda(
-    list: PublicKey,
-    subject: PublicKey
-  ): PublicKey {
-    return PublicKey.findProgramAddressSync(
-      [Buffer.from("entry"), list.toBuffer(), subject.toBuffer()],
-      BOUNCER_PROGRAM_ID
-    )[0];
-  }
-
   // ------------------------------------------------------------
   // PDA derivations
   // ------------------------------------------------------------
 
-  // Config PDA (global, one per hook program)
+  // Config PDA (per mint; the global ["config"] PDA is only a legacy fallback)
   const [configPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("config")],
+    [Buffer.from("config"), MINT.toBuffer()],
     program.programId
   );
 
@@ -109,10 +94,10 @@ import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
     
     try {
       const tx = await program.methods
-        .updateConfig(BOUNCER_PROGRAM_ID, BOUNCER_LIST)
-        .accounts({
-          payer,
-          config: configPda,
+        .updateMintConfig(BOUNCER_PROGRAM_ID, BOUNCER_LIST, null)
+        .accountsPartial({
+          admin: payer,
+          mintConfig: configPda,
         })
         .rpc();
 
@@ -128,10 +113,12 @@ import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
     console.log("\nInitializing new config...");
     try {
       const tx = await program.methods
-        .initializeConfig(BOUNCER_PROGRAM_ID, BOUNCER_LIST)
-        .accounts({
+        .initializeMintConfig(BOUNCER_PROGRAM_ID, BOUNCER_LIST)
+        .accountsPartial({
           payer,
-          config: configPda,
+          mintAuthority: payer,
+          mint: MINT,
+          mintConfig: configPda,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -150,12 +137,13 @@ import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
   console.log("\nInitializing ExtraAccountMetaList...");
   console.log("This will set up the extra accounts needed for transfer hook execution.");
 
-  // Derive entry account PDA for the example whitelisted PDA
-  // Note: This is just for initialization - the actual entry account used during transfers
-  // will be dynamically derived based on the destination
-  const exampleEntryPda = entryPda(BOUNCER_LIST, EXAMPLE_WHITELISTED_PDA);
-  console.log("Example Entry PDA (for initialization):", exampleEntryPda.toBase58());
-  console.log("Example Subject (whitelisted PDA):", EXAMPLE_WHITELISTED_PDA.toBase58());
+  // The bouncer entry account is not passed here: it is derived per transfer
+  // from ["entry", bouncer list, destination owner] by the ExtraAccountMetaList
+  // (["proof", bouncer list, owner] for Merkle lists, written by each holder
+  // with bouncer's writeProofBuffer before transferring). The sender's
+  // ["tracker", mint, owner] PDA is resolved the same way; it only has to exist
+  // (initializeTransferTracker) once the mint has a velocity limit. The
+  // recipient's ["balance", mint, owner] override is optional.
 
   try {
     const tx = await program.methods
@@ -167,7 +155,6 @@ import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
         config: configPda,
         bouncerProgram: BOUNCER_PROGRAM_ID,
         bouncerList: BOUNCER_LIST,
-        entryAccount: exampleEntryPda,
         tokenProgram: TOKEN_2022_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -359,21 +359,20 @@ describe("Transfer Hook Test", () => {
     const mintInfo = await getMint(connection, TOKEN_MINT, undefined, TOKEN_2022_PROGRAM_ID);
     const decimals = mintInfo.decimals;
 
-    // Get config PDA
+    // Get the mint's config PDA (the global ["config"] PDA is only a legacy fallback)
     const [configPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("config")],
+      [Buffer.from("config"), TOKEN_MINT.toBuffer()],
       TRANSFER_HOOK_PROGRAM_ID
     );
-    console.log("Config PDA:", configPda.toBase58());
+    console.log("Mint config PDA:", configPda.toBase58());
 
     // Fetch config to verify it exists
     try {
-      const config = await transferHookProgram.account.config.fetch(configPda);
-      console.log("Config found", config);
-      // Note: Config structure may vary - adjust based on actual structure
+      const config = await transferHookProgram.account.mintConfig.fetch(configPda);
+      console.log("Mint config found", config);
     } catch (error) {
-      console.error("Error fetching config:", error);
-      throw new Error("Config not initialized. Please initialize config first.");
+      console.error("Error fetching mint config:", error);
+      throw new Error("Mint config not initialized. Please run scripts/init.ts first.");
     }
 
     // Build transfer instruction with extra accounts
@@ -513,9 +512,9 @@ describe("Transfer Hook Test", () => {
       throw new Error("Transfer should have been blocked");
     } catch (error: any) {
       console.log("Error:", error);
-      if (error.message.includes("Transfer not allowed") || 
+      if (error.message.includes("Recipient is not on the allowlist") || 
           error.message.includes("custom program error") ||
-          error.logs?.some((log: string) => log.includes("Transfer not allowed"))) {
+          error.logs?.some((log: string) => log.includes("Recipient is not on the allowlist"))) {
         console.log("✓ Transfer correctly blocked by transfer hook");
         if (error.logs) {
           console.log("Program logs:");